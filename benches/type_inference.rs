@@ -15,6 +15,7 @@ fn create_test_data_for_type(sql_type: &SqlType, rows: usize) -> String {
                     "false"
                 }
             }
+            SqlType::TinyInt => &(i as i8 % 100).to_string(),
             SqlType::SmallInt => &(i as i16 % 100).to_string(),
             SqlType::Integer => &(i as i32).to_string(),
             SqlType::BigInt => &(i as i64).to_string(),
@@ -22,6 +23,9 @@ fn create_test_data_for_type(sql_type: &SqlType, rows: usize) -> String {
             SqlType::Date => "2024-01-15",
             SqlType::Time => "14:30:25",
             SqlType::DateTime => "2024-01-15 14:30:25",
+            SqlType::DateTimeTz => "2024-01-15T14:30:25+02:00",
+            SqlType::Decimal { .. } => &format!("{}.{:02}", i, i % 100),
+            SqlType::Json => &format!("[{}]", i),
             SqlType::Varchar(_) => &format!("string_value_{}", i),
         };
         csv.push_str(value);
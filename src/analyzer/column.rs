@@ -1,18 +1,96 @@
-use crate::analyzer::patterns::TypeInferencer;
-use crate::types::{ColumnStats, SqlType};
+use crate::analyzer::distinct::DistinctCounter;
+use crate::analyzer::distribution::NumberDistribution;
+use crate::analyzer::frequency::TopValues;
+use crate::analyzer::lattice::PromotionLattice;
+use crate::analyzer::nulls::NullPolicy;
+use crate::analyzer::overrides::ColumnOverride;
+use crate::analyzer::patterns::{decimal_digits, fractional_second_digits, TypeInferencer};
+use crate::analyzer::range::ValueRange;
+use crate::analyzer::sentinels::ValueProfile;
+use crate::analyzer::tolerance::FamilyTally;
+use crate::analyzer::type_set::{TypeSet, TypeSize};
+use crate::cli::{BlankPolicy, NumericIdPolicy, Tolerance};
+use crate::types::{
+    ColumnStats, DistinctCount, ExpectedReject, LengthSummary, SqlType, TypeEvidence, TypePromotion,
+};
+use chrono::NaiveTime;
 use log;
-use std::collections::HashSet;
+use std::borrow::Cow;
+
+/// Common spellings of a missing value, compared in lowercase, that are
+/// reported when they are analyzed as data
+const NULL_LIKE_VALUES: [&str; 10] = [
+    "", "null", "none", "nil", "na", "n/a", "#n/a", "nan", "(null)", "\\n",
+];
 
 const MAX_SAMPLE_VALUES: usize = 10;
-const MAX_UNIQUE_VALUES: usize = 1000;
+/// Values quoted when explaining why a column was kept as text
+const MAX_IDENTIFIER_SAMPLES: usize = 5;
+/// Example values kept for each type in the column's type evidence
+const MAX_TYPE_EXAMPLES: usize = 3;
+/// Buckets in the histogram of a numeric or temporal column
+const HISTOGRAM_BUCKETS: usize = 10;
+
+/// Values of one kind seen so far, see `TypeEvidence`
+#[derive(Debug)]
+struct KindEvidence {
+    kind: TypeSet,
+    count: usize,
+    first_row: usize,
+    examples: Vec<String>,
+    size: TypeSize,
+}
 
 #[derive(Debug)]
 pub struct ColumnAnalyzer {
     stats: ColumnStats,
     inferencer: TypeInferencer,
-    null_values: HashSet<String>,
-    unique_values: HashSet<String>,
-    first_non_null_type: Option<SqlType>,
+    nulls: NullPolicy,
+    distinct: DistinctCounter,
+    /// Most distinct values of a categorical column, when categories are wanted
+    max_categories: Option<usize>,
+    /// Most frequent values, kept only when asked for with `--top`
+    top_values: Option<TopValues>,
+    /// Types accepted by every non-null value so far; `None` before the first one
+    candidates: Option<TypeSet>,
+    /// The type each value would get on its own, over all values so far
+    observed: TypeSet,
+    lattice: PromotionLattice,
+    /// Most integer and fractional digits of the exact numeric values
+    int_digits: usize,
+    frac_digits: usize,
+    /// Shortest non-null value, to spot fixed-width identifiers
+    min_length: Option<usize>,
+    /// Shortest and longest value in characters, and the sum of all lengths
+    min_chars: usize,
+    max_chars: usize,
+    total_chars: usize,
+    /// Mean, variance and quantiles of the numeric values
+    numbers: NumberDistribution,
+    /// Values with leading zeros or a `+` sign that were kept as text
+    identifier_samples: Vec<String>,
+    /// Most fractional-second digits of any time or timestamp value
+    second_digits: usize,
+    /// Date formats, as a `TypeInferencer::matching_date_formats` mask, that read
+    /// every date value so far; `None` before the first one
+    date_formats: Option<u32>,
+    /// Timestamps without and with a time zone offset
+    naive_timestamps: usize,
+    zoned_timestamps: usize,
+    /// Count, first row and examples of each kind of value
+    evidence: Vec<KindEvidence>,
+    /// Distinct values by kind, to look for NULL sentinels
+    profile: ValueProfile,
+    /// Lowest and highest values in each domain, for the final type's min and max
+    range: ValueRange,
+    /// How many values may be rejected rather than widen the type, and the
+    /// values of each type family, kept only when a tolerance is set
+    tolerance: Option<Tolerance>,
+    tally: Option<FamilyTally>,
+    /// Settings from `--overrides` that win over inference
+    forced: ColumnOverride,
+    /// The first value the forced type cannot hold
+    forced_conflict: Option<String>,
     verbose: bool,
     current_row: usize,
 }
@@ -24,48 +102,136 @@ impl ColumnAnalyzer {
         null_values: Vec<String>,
         verbose: bool,
     ) -> Self {
-        let mut null_set = HashSet::new();
-        null_set.insert("".to_string());
-        null_set.insert("NULL".to_string());
-        null_set.insert("null".to_string());
-
-        for val in null_values {
-            null_set.insert(val);
-        }
-
         ColumnAnalyzer {
             stats: ColumnStats::new(name),
             inferencer,
-            null_values: null_set,
-            unique_values: HashSet::new(),
-            first_non_null_type: None,
+            nulls: NullPolicy::new(null_values),
+            distinct: DistinctCounter::default(),
+            max_categories: None,
+            top_values: None,
+            candidates: None,
+            observed: TypeSet::EMPTY,
+            lattice: PromotionLattice::default(),
+            int_digits: 0,
+            frac_digits: 0,
+            min_length: None,
+            min_chars: usize::MAX,
+            max_chars: 0,
+            total_chars: 0,
+            numbers: NumberDistribution::default(),
+            identifier_samples: Vec::new(),
+            second_digits: 0,
+            date_formats: None,
+            naive_timestamps: 0,
+            zoned_timestamps: 0,
+            evidence: Vec::new(),
+            profile: ValueProfile::default(),
+            range: ValueRange::default(),
+            tolerance: None,
+            tally: None,
+            forced: ColumnOverride::default(),
+            forced_conflict: None,
             verbose,
             current_row: 0,
         }
     }
 
+    /// Force the override's type, nullability and date format, and read its null tokens
+    pub fn with_override(mut self, forced: ColumnOverride) -> Self {
+        self.nulls = self.nulls.with_override(&forced);
+        if let Some(format) = &forced.date_format {
+            self.inferencer = self.inferencer.with_date_format(format.clone());
+        }
+        self.forced = forced;
+        self
+    }
+
+    /// Whether `DEFAULT_NULL_VALUES` are NULL besides the explicit tokens
+    pub fn with_default_nulls(mut self, default_nulls: bool) -> Self {
+        self.nulls = self.nulls.with_default_nulls(default_nulls);
+        self
+    }
+
+    /// The input format's own NULL token, read as NULL whatever the overrides say
+    pub fn with_input_null(mut self, token: Option<&'static str>) -> Self {
+        self.nulls = self.nulls.with_input_null(token);
+        self
+    }
+
+    /// How values made only of whitespace are read
+    pub fn with_blank_values(mut self, policy: BlankPolicy) -> Self {
+        self.nulls = self.nulls.with_blank_values(policy);
+        self
+    }
+
+    /// Keep a narrow type when at most `tolerance` values disagree with it
+    pub fn with_tolerance(mut self, tolerance: Option<Tolerance>) -> Self {
+        self.tolerance = tolerance;
+        self.tally = tolerance.map(|_| FamilyTally::default());
+        self
+    }
+
+    /// Count up to `limit` distinct values exactly before estimating; 0 always estimates
+    pub fn with_exact_distinct(mut self, limit: usize) -> Self {
+        self.distinct = DistinctCounter::new(limit).with_values(self.max_categories);
+        self
+    }
+
+    /// Keep the values of text columns with at most `max` distinct ones, for
+    /// categorical constraints
+    pub fn with_categories(mut self, max: Option<usize>) -> Self {
+        self.max_categories = max;
+        self.distinct = self.distinct.with_values(max);
+        self
+    }
+
+    /// Keep the `top` most frequent values and a histogram of the column
+    pub fn with_top(mut self, top: Option<usize>) -> Self {
+        self.top_values = top.map(TopValues::new);
+        if top.is_some() {
+            self.range = ValueRange::default().with_histograms();
+        }
+        self
+    }
+
+    /// Join mixed value types with the given promotion rules
+    pub fn with_lattice(mut self, lattice: PromotionLattice) -> Self {
+        self.lattice = lattice;
+        self
+    }
+
     pub fn analyze_value(&mut self, value: &str, row_number: usize) {
         self.current_row = row_number;
         self.stats.total_count += 1;
 
-        let trimmed = value.trim();
-
-        // Check for null values
-        if self.is_null_value(trimmed) {
+        let blank = !value.is_empty() && value.trim().is_empty();
+        let Some(trimmed) = self.read_value(value) else {
             self.stats.null_count += 1;
             return;
-        }
+        };
 
-        // Track unique values (with limit to prevent memory explosion)
-        if self.unique_values.len() < MAX_UNIQUE_VALUES {
-            self.unique_values.insert(trimmed.to_string());
+        // Count values that look missing but are analyzed as data
+        if blank || NULL_LIKE_VALUES.contains(&trimmed.to_ascii_lowercase().as_str()) {
+            *self
+                .stats
+                .null_like_values
+                .entry(trimmed.to_string())
+                .or_insert(0) += 1;
         }
 
-        // Update max length
-        self.stats.max_length = self.stats.max_length.max(trimmed.len());
+        self.distinct.insert(trimmed);
+        if let Some(top_values) = &mut self.top_values {
+            top_values.record(trimmed);
+        }
 
-        // Update min/max values for ordering
-        self.update_min_max(trimmed);
+        // Update min and max length
+        let len = trimmed.len();
+        self.stats.max_length = self.stats.max_length.max(len);
+        self.min_length = Some(self.min_length.map_or(len, |l| l.min(len)));
+        let chars = trimmed.chars().count();
+        self.min_chars = self.min_chars.min(chars);
+        self.max_chars = self.max_chars.max(chars);
+        self.total_chars += chars;
 
         // Add to sample values
         if self.stats.sample_values.len() < MAX_SAMPLE_VALUES
@@ -74,107 +240,286 @@ impl ColumnAnalyzer {
             self.stats.sample_values.push(trimmed.to_string());
         }
 
-        // Infer type and potentially promote
-        let inferred_type = self.inferencer.infer_type(trimmed);
-        self.update_type(inferred_type, trimmed);
-    }
+        // Track the digits a DECIMAL would need
+        if let Some((int_digits, frac_digits)) = decimal_digits(trimmed) {
+            self.int_digits = self.int_digits.max(int_digits);
+            self.frac_digits = self.frac_digits.max(frac_digits);
+        }
 
-    fn is_null_value(&self, value: &str) -> bool {
-        self.null_values.contains(value)
-    }
+        // Remember identifiers a number would corrupt, to explain the text type
+        if self.identifier_samples.len() < MAX_IDENTIFIER_SAMPLES
+            && self.inferencer.numeric_ids() != NumericIdPolicy::Never
+            && self.inferencer.is_identifier_like(trimmed)
+            && !self.identifier_samples.iter().any(|v| v == trimmed)
+        {
+            self.identifier_samples.push(trimmed.to_string());
+        }
 
-    fn update_min_max(&mut self, value: &str) {
-        match (&self.stats.min_value, &self.stats.max_value) {
-            (None, None) => {
-                self.stats.min_value = Some(value.to_string());
-                self.stats.max_value = Some(value.to_string());
+        // Narrow the candidate types to those that also accept this value
+        let value_types = self.inferencer.candidate_types(trimmed);
+        self.record_evidence(value_types, trimmed, row_number);
+        self.profile.record(trimmed, value_types, &self.inferencer);
+        self.range.record(trimmed, value_types, &self.inferencer);
+        if !(value_types & TypeSet::NUMERIC).is_empty() {
+            if let Ok(number) = trimmed.parse::<f64>() {
+                self.numbers.record(number);
             }
-            (Some(min), Some(max)) => {
-                if value < min {
-                    self.stats.min_value = Some(value.to_string());
-                }
-                if value > max {
-                    self.stats.max_value = Some(value.to_string());
-                }
+        }
+        if let Some(tally) = &mut self.tally {
+            tally.record(row_number, trimmed, value_types);
+        }
+        match value_types.narrowest() {
+            Some(TypeSet::DATETIME) => self.naive_timestamps += 1,
+            Some(TypeSet::DATETIMETZ) => self.zoned_timestamps += 1,
+            _ => {}
+        }
+        // Remember the first value a forced type cannot hold
+        let forced_kind = self.forced.sql_type.as_ref().and_then(TypeSet::kind_of);
+        if let Some(kind) = forced_kind {
+            if self.forced_conflict.is_none() && !value_types.contains(kind) {
+                self.forced_conflict = Some(format!("row {} has '{}'", row_number, trimmed));
+            }
+        }
+
+        if value_types.contains(TypeSet::DATE) {
+            let formats = self.inferencer.matching_date_formats(trimmed);
+            self.date_formats = Some(self.date_formats.map_or(formats, |f| f & formats));
+            self.range.record_date(trimmed, formats, &self.inferencer);
+        }
+        if value_types.contains(TypeSet::TIME) || value_types.contains(TypeSet::DATETIMETZ) {
+            self.second_digits = self.second_digits.max(fractional_second_digits(trimmed));
+        }
+        self.update_type(value_types, trimmed);
+    }
+
+    fn record_evidence(&mut self, value_types: TypeSet, value: &str, row_number: usize) {
+        let kind = value_types.narrowest().unwrap_or(TypeSet::VARCHAR);
+        let index = match self.evidence.iter().position(|e| e.kind == kind) {
+            Some(index) => index,
+            None => {
+                self.evidence.push(KindEvidence {
+                    kind,
+                    count: 0,
+                    first_row: row_number,
+                    examples: Vec::new(),
+                    size: TypeSize::default(),
+                });
+                self.evidence.len() - 1
             }
-            _ => {} // Should not happen
+        };
+
+        let evidence = &mut self.evidence[index];
+        evidence.count += 1;
+        evidence.size.widen(value);
+        if evidence.examples.len() < MAX_TYPE_EXAMPLES
+            && !evidence.examples.iter().any(|v| v == value)
+        {
+            evidence.examples.push(value.to_string());
+        }
+    }
+
+    /// The value as this column reads it, or `None` if it is NULL
+    pub fn read_value<'a>(&self, value: &'a str) -> Option<&'a str> {
+        self.nulls.read(value)
+    }
+
+    /// The value as keys compare it, or `None` if it is NULL. Numbers, dates
+    /// and timestamps are written one way, so `1.5` and `1.50` are the same
+    /// value and so are `2024-01-01T10:00:00` and `2024-01-01 10:00:00`;
+    /// merging values can hide a key but never makes one up.
+    pub fn key_value<'a>(&self, value: &'a str) -> Option<Cow<'a, str>> {
+        let value = self.read_value(value)?;
+        if decimal_digits(value).is_some() {
+            return Some(canonical_number(value));
         }
+        let instant = self
+            .inferencer
+            .parse_datetime_tz(value)
+            .map(|t| t.naive_utc())
+            .or_else(|| self.inferencer.parse_datetime(value))
+            .or_else(|| {
+                self.inferencer
+                    .parse_date(value)
+                    .map(|d| d.and_time(NaiveTime::MIN))
+            });
+        Some(match instant {
+            Some(instant) => Cow::Owned(instant.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+            None => Cow::Borrowed(value),
+        })
     }
 
-    fn update_type(&mut self, new_type: SqlType, value: &str) {
-        // If this is our first non-null value, set the initial type
-        if self.first_non_null_type.is_none() {
-            self.first_non_null_type = Some(new_type.clone());
-            self.stats.sql_type = new_type.clone();
+    fn update_type(&mut self, value_types: TypeSet, value: &str) {
+        // An exact number too long for the dialect's DECIMAL can only be text,
+        // as DOUBLE PRECISION would round it. The column's DECIMAL needs the most
+        // integer digits plus the most fractional digits, so values that fit
+        // alone can overflow together.
+        let max_digits = self.inferencer.max_decimal_digits();
+        let column_digits = self.int_digits + self.frac_digits;
+        let overflow = match self.inferencer.decimal_overflow(value) {
+            Some(digits) => Some(format!(
+                "needs {} digits, more than the DECIMAL maximum of {}",
+                digits, max_digits
+            )),
+            None if value_types.contains(TypeSet::DECIMAL) && column_digits > max_digits => {
+                Some(format!(
+                    "the column needs {} digits ({} integer, {} fractional), more than the DECIMAL maximum of {}",
+                    column_digits, self.int_digits, self.frac_digits, max_digits
+                ))
+            }
+            None => None,
+        };
+        let value_types = match overflow {
+            Some(_) if self.inferencer.exact_decimals() => {
+                value_types - TypeSet::DECIMAL - TypeSet::DOUBLE
+            }
+            Some(_) => value_types - TypeSet::DECIMAL,
+            None => value_types,
+        };
+
+        let value_kind = value_types.narrowest().unwrap_or(TypeSet::VARCHAR);
+        let new_kind = !self.observed.contains(value_kind);
+        self.observed |= value_kind;
+
+        // If this is our first non-null value, its types are the initial candidates
+        let Some(candidates) = self.candidates else {
+            self.candidates = Some(value_types);
+            self.stats.sql_type = value_kind.sql_type(self.type_size());
+            let reason = overflow.map(|o| format!(" ({})", o)).unwrap_or_default();
 
             if self.verbose {
                 eprintln!(
-                    "Column '{}' initial type set to {} on row {} with value: '{}'",
-                    self.stats.name, new_type, self.current_row, value
+                    "Column '{}' initial type set to {} on row {} with value: '{}'{}",
+                    self.stats.name, self.stats.sql_type, self.current_row, value, reason
                 );
             }
 
             // Also log for RUST_LOG debug mode
             log::debug!(
-                "Column '{}' initial type set to {} on row {} with value: '{}'",
+                "Column '{}' initial type set to {} on row {} with value: '{}'{}",
                 self.stats.name,
-                new_type,
+                self.stats.sql_type,
                 self.current_row,
-                value
+                value,
+                reason
             );
             return;
-        }
+        };
 
-        // Check if we need to promote the type
-        if self.stats.sql_type != new_type {
-            let mut promoted_type = self.stats.sql_type.promote(&new_type);
+        // VARCHAR accepts every value, so the intersection is never empty
+        let remaining = candidates & value_types;
+        let rejected = candidates - remaining;
+        self.candidates = Some(remaining);
 
-            // If we're promoting to VARCHAR, ensure it can accommodate the maximum length seen so far
-            if let SqlType::Varchar(Some(size)) = &promoted_type {
-                if self.stats.max_length > *size {
-                    promoted_type = SqlType::Varchar(Some(self.stats.max_length));
-                }
-            }
+        // The lattice picks the type for the mix of values seen, but only a
+        // candidate is guaranteed to accept them all
+        let (joined, rule) = self.lattice.join_all(self.observed);
+        let kind = if remaining.contains(joined) {
+            joined
+        } else {
+            remaining.narrowest().unwrap_or(TypeSet::VARCHAR)
+        };
 
-            if promoted_type != self.stats.sql_type {
-                // Log the promotion
-                let promotion_msg = format!(
-                    "Row {}: promoted from {} to {} due to value: '{}'",
-                    self.current_row, self.stats.sql_type, promoted_type, value
-                );
-                self.stats.type_promotions.push(promotion_msg.clone());
+        // A VARCHAR grows to the longest value seen, a DECIMAL to the most digits
+        let new_type = kind.sql_type(self.type_size());
 
+        if new_kind || !rejected.is_empty() {
+            let mut reasons: Vec<String> = overflow.into_iter().collect();
+            match rule {
+                Some(rule) if kind == joined => reasons.push(format!("rule {}", rule)),
+                Some(rule) => reasons.push(format!("rule {} does not fit every value", rule)),
+                None => {}
+            }
+            if !rejected.is_empty() {
+                reasons.push(format!("rejects {}", rejected));
+            }
+            if new_type.promotion_order() != self.stats.sql_type.promotion_order() {
+                let promotion = TypePromotion {
+                    row: self.current_row,
+                    from: self.stats.sql_type.clone(),
+                    to: new_type.clone(),
+                    value: value.to_string(),
+                    reasons,
+                };
                 if self.verbose {
-                    eprintln!("Column '{}' {}", self.stats.name, promotion_msg);
+                    eprintln!("Column '{}' {}", self.stats.name, promotion);
                 }
-
-                // Also log for RUST_LOG debug mode
-                log::debug!("Column '{}' {}", self.stats.name, promotion_msg);
-
-                self.stats.sql_type = promoted_type;
+                log::debug!("Column '{}' {}", self.stats.name, promotion);
+                self.stats.promotions.push(promotion);
+            } else {
+                let reasons = if reasons.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", reasons.join("; "))
+                };
+                self.note(format!(
+                    "Row {}: kept {} for value: '{}'{}",
+                    self.current_row, new_type, value, reasons
+                ));
             }
         }
 
-        // Handle VARCHAR sizing
-        if let SqlType::Varchar(current_size) = &self.stats.sql_type {
-            if let SqlType::Varchar(new_size) = &new_type {
-                match (current_size, new_size) {
-                    (Some(current), Some(new)) => {
-                        if *new > *current {
-                            self.stats.sql_type = SqlType::Varchar(Some(*new));
-                        }
-                    }
-                    (Some(_), None) => {
-                        self.stats.sql_type = SqlType::Varchar(None);
-                    }
-                    _ => {} // Keep current
-                }
-            }
+        self.stats.sql_type = new_type;
+    }
+
+    fn type_size(&self) -> TypeSize {
+        TypeSize {
+            max_length: self.stats.max_length,
+            int_digits: self.int_digits,
+            frac_digits: self.frac_digits,
         }
     }
 
     pub fn finalize(&mut self) {
+        self.stats.type_evidence = self
+            .evidence
+            .iter()
+            .map(|e| TypeEvidence {
+                sql_type: e.kind.sql_type(e.size),
+                count: e.count,
+                first_row: e.first_row,
+                examples: e.examples.clone(),
+            })
+            .collect();
+        self.stats
+            .type_evidence
+            .sort_by_key(|e| e.sql_type.promotion_order());
+
+        self.find_sentinels();
+        self.tolerate_rejects();
+
         // Final adjustments to type based on statistics
+        self.keep_identifiers_as_text();
+
+        self.choose_date_format();
+        self.size_integers_by_range();
+
+        if !self.stats.null_like_values.is_empty() {
+            let values: Vec<String> = self
+                .stats
+                .null_like_values
+                .iter()
+                .map(|(value, count)| format!("'{}' ({})", value, count))
+                .collect();
+            self.note(format!(
+                "Analyzed null-like values as data: {}",
+                values.join(", ")
+            ));
+        }
+
+        // Keep the fractional seconds of TIME and TIMESTAMP columns in the DDL
+        let temporal = matches!(
+            self.stats.sql_type,
+            SqlType::Time | SqlType::DateTime | SqlType::DateTimeTz
+        );
+        self.stats.time_precision =
+            (temporal && self.second_digits > 0).then(|| self.second_digits.min(9) as u8);
+
+        if self.stats.sql_type == SqlType::DateTimeTz && self.naive_timestamps > 0 {
+            self.note(format!(
+                "Mixes {} zoned and {} naive timestamps; naive values have no offset and are taken as UTC",
+                self.zoned_timestamps, self.naive_timestamps
+            ));
+        }
 
         // If we have a VARCHAR with a size, consider if we should make it unlimited
         if let SqlType::Varchar(Some(size)) = &self.stats.sql_type {
@@ -188,6 +533,304 @@ impl ColumnAnalyzer {
         if let SqlType::Varchar(Some(0)) = &self.stats.sql_type {
             self.stats.sql_type = SqlType::Varchar(Some(1));
         }
+
+        self.apply_override();
+        self.summarize_values();
+        // An estimate can overshoot the number of values
+        let non_null = self.stats.total_count - self.stats.null_count;
+        self.stats.distinct = Some(DistinctCount {
+            count: self.distinct.count().min(non_null),
+            exact: self.distinct.is_exact(),
+        });
+    }
+
+    /// Size an integer column by the lowest and highest of its values
+    fn size_integers_by_range(&mut self) {
+        if !matches!(
+            self.stats.sql_type,
+            SqlType::SmallInt | SqlType::Integer | SqlType::BigInt
+        ) {
+            return;
+        }
+        let Some(kind) = self.range.integer_kind() else {
+            return;
+        };
+        let sql_type = kind.sql_type(self.type_size());
+        if sql_type != self.stats.sql_type {
+            let (low, high) = self.range.bounds(kind, None).unwrap_or_default();
+            self.note(format!(
+                "Sized {} by its range {} to {}",
+                sql_type, low, high
+            ));
+            self.stats.sql_type = sql_type;
+        }
+    }
+
+    /// Min, max, histogram and numeric summary in the domain of the final
+    /// type, value lengths and the top values
+    fn summarize_values(&mut self) {
+        let kind = TypeSet::kind_of(&self.stats.sql_type).unwrap_or(TypeSet::VARCHAR);
+        // A forced date format is tried first, as bit 0 of the mask
+        let date_format = match self.forced.date_format {
+            Some(_) => Some(0),
+            None => self.date_formats.map(|mask| mask.trailing_zeros() as usize),
+        };
+        if let Some((low, high)) = self.range.bounds(kind, date_format) {
+            self.stats.min_value = Some(low.to_string());
+            self.stats.max_value = Some(high.to_string());
+        }
+
+        if TypeSet::NUMERIC.contains(kind) {
+            self.stats.numeric = self.numbers.summary();
+        }
+        let non_null = self.stats.total_count - self.stats.null_count;
+        if non_null > 0 {
+            self.stats.length = Some(LengthSummary {
+                min: self.min_chars,
+                avg: self.total_chars as f64 / non_null as f64,
+                max: self.max_chars,
+            });
+        }
+
+        if let Some(max) = self.max_categories {
+            if kind == TypeSet::VARCHAR && self.is_likely_categorical(max) {
+                // Kept only while there were at most `max` of them
+                self.stats.categories = self.distinct.values().unwrap_or_default();
+            }
+        }
+
+        if let Some(top_values) = &self.top_values {
+            self.stats.top_values = top_values.values();
+            self.stats.histogram =
+                self.range
+                    .histogram(kind, date_format, &self.inferencer, HISTOGRAM_BUCKETS);
+        }
+    }
+
+    /// Flag values that probably stand for NULL, with the type the column would
+    /// get without them
+    fn find_sentinels(&mut self) {
+        let (sentinels, without) = self.profile.sentinels(&self.lattice, &self.inferencer);
+        if sentinels.is_empty() {
+            return;
+        }
+
+        let values: Vec<String> = sentinels
+            .iter()
+            .map(|s| format!("'{}' x{} ({})", s.value, s.count, s.reason))
+            .collect();
+        let as_null = without
+            .as_ref()
+            .map(|t| format!("; as NULL the column would be {}", t))
+            .unwrap_or_default();
+        self.note(format!(
+            "Possible null sentinels: {}{}",
+            values.join(", "),
+            as_null
+        ));
+        self.stats.sentinels = sentinels;
+        self.stats.type_without_sentinels = without;
+    }
+
+    /// Keep the type all but a tolerated few values fit, listing those few as
+    /// the rows `parse --schema` will reject
+    fn tolerate_rejects(&mut self) {
+        let (Some(tolerance), Some(tally)) = (self.tolerance, &self.tally) else {
+            return;
+        };
+        let allowed = tolerance.allowed(self.stats.total_count - self.stats.null_count);
+        let Some((sql_type, rejects)) = tally.tolerated_type(allowed, &self.lattice) else {
+            return;
+        };
+        if rejects.is_empty() || sql_type == self.stats.sql_type {
+            return;
+        }
+
+        let expected: Vec<ExpectedReject> = rejects
+            .iter()
+            .map(|(row, value)| ExpectedReject {
+                line: row + 1,
+                value: value.clone(),
+            })
+            .collect();
+        self.note(format!(
+            "Kept {} instead of {} within the tolerance; {} values will be rejected",
+            sql_type,
+            self.stats.sql_type,
+            expected.len()
+        ));
+        self.stats.sql_type = sql_type;
+        self.stats.expected_rejects = expected;
+    }
+
+    /// Replace inferred settings with the forced ones, warning where the data disagrees
+    fn apply_override(&mut self) {
+        if let Some(sql_type) = self.forced.sql_type.clone() {
+            if let Some(conflict) = self.forced_type_conflict(&sql_type) {
+                self.warn(format!("is forced to {}, but {}", sql_type, conflict));
+            }
+            if !matches!(
+                sql_type,
+                SqlType::Time | SqlType::DateTime | SqlType::DateTimeTz
+            ) {
+                self.stats.time_precision = None;
+            }
+            if sql_type != SqlType::Date {
+                self.stats.date_format = None;
+            }
+            self.stats.sql_type = sql_type;
+        }
+
+        match self.forced.not_null {
+            Some(true) => {
+                if self.stats.null_count > 0 {
+                    self.warn(format!(
+                        "is forced NOT NULL, but has {} null values",
+                        self.stats.null_count
+                    ));
+                }
+                self.stats.nullable = Some(false);
+            }
+            Some(false) => self.stats.nullable = Some(true),
+            None => {}
+        }
+
+        if let Some(format) = self.forced.date_format.clone() {
+            // The override's format is tried first, as bit 0 of the mask
+            if self.date_formats.is_some_and(|mask| mask & 1 == 0) {
+                self.warn(format!(
+                    "is forced to date format {}, but not every value fits it",
+                    format
+                ));
+            }
+            self.stats.date_format = Some(format);
+        }
+    }
+
+    /// Why the values do not fit a forced type, if they do not
+    fn forced_type_conflict(&self, sql_type: &SqlType) -> Option<String> {
+        if let Some(conflict) = &self.forced_conflict {
+            return Some(conflict.clone());
+        }
+        match sql_type {
+            SqlType::Varchar(Some(size)) if self.stats.max_length > *size => Some(format!(
+                "values are up to {} characters long",
+                self.stats.max_length
+            )),
+            SqlType::Decimal { precision, scale }
+                if self.int_digits > (precision - scale) as usize
+                    || self.frac_digits > *scale as usize =>
+            {
+                Some(format!(
+                    "values need {} integer and {} fractional digits",
+                    self.int_digits, self.frac_digits
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Report data that contradicts an override, verbose or not
+    fn warn(&mut self, message: String) {
+        eprintln!("Warning: column '{}' {}", self.stats.name, message);
+        self.stats
+            .notes
+            .push(format!("Override conflict: {}", message));
+    }
+
+    /// Explain identifier-like values kept as text and, under the fixed-width
+    /// policy, turn an integer column whose values all share one width into text
+    fn keep_identifiers_as_text(&mut self) {
+        let is_integer = matches!(
+            self.stats.sql_type,
+            SqlType::TinyInt
+                | SqlType::SmallInt
+                | SqlType::Integer
+                | SqlType::BigInt
+                | SqlType::Decimal { scale: 0, .. }
+        );
+        let width = self.stats.max_length;
+
+        let message = if !self.identifier_samples.is_empty()
+            && matches!(self.stats.sql_type, SqlType::Varchar(_))
+        {
+            format!(
+                "Kept {} for identifier-like values: {} (a number would drop their leading zeros or + sign)",
+                self.stats.sql_type,
+                quote_values(&self.identifier_samples)
+            )
+        } else if is_integer
+            && self.inferencer.numeric_ids() == NumericIdPolicy::FixedWidth
+            && width > 1
+            && self.min_length == Some(width)
+        {
+            self.stats.sql_type = SqlType::Varchar(Some(width));
+            let samples: Vec<String> = self
+                .stats
+                .sample_values
+                .iter()
+                .take(MAX_IDENTIFIER_SAMPLES)
+                .cloned()
+                .collect();
+            format!(
+                "Kept {} for fixed-width values: {} (every value has {} characters)",
+                self.stats.sql_type,
+                quote_values(&samples),
+                width
+            )
+        } else {
+            return;
+        };
+
+        self.note(message);
+    }
+
+    /// Pick the format that reads every value of a DATE column; a column whose
+    /// values need different formats can only be loaded as text
+    fn choose_date_format(&mut self) {
+        if self.stats.sql_type != SqlType::Date {
+            return;
+        }
+
+        match self.date_formats {
+            Some(0) => {
+                self.stats.sql_type = SqlType::Varchar(Some(self.stats.max_length.max(1)));
+                self.note(format!(
+                    "Kept {} because no single date format reads every value",
+                    self.stats.sql_type
+                ));
+            }
+            Some(mask) => {
+                let chosen = mask.trailing_zeros() as usize;
+                let format = self.inferencer.date_format_at(chosen).to_string();
+                let others: Vec<&str> = (chosen + 1..u32::BITS as usize)
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| self.inferencer.date_format_at(i))
+                    .collect();
+                // A forced format needs no assumption
+                if !others.is_empty() && self.forced.date_format.is_none() {
+                    let message = format!(
+                        "Assumed date format {}; every value also fits {}",
+                        format,
+                        others.join(", ")
+                    );
+                    self.note(message);
+                }
+                self.stats.date_format = Some(format);
+            }
+            None => {}
+        }
+    }
+
+    /// Record a note about the column's type, shown in verbose mode
+    fn note(&mut self, message: String) {
+        if self.verbose {
+            eprintln!("Column '{}' {}", self.stats.name, message);
+        }
+
+        // Also log for RUST_LOG debug mode
+        log::debug!("Column '{}' {}", self.stats.name, message);
+        self.stats.notes.push(message);
     }
 
     pub fn get_stats(&self) -> &ColumnStats {
@@ -199,40 +842,85 @@ impl ColumnAnalyzer {
     }
 
     pub fn unique_value_count(&self) -> usize {
-        self.unique_values.len()
+        self.distinct.count()
     }
 
     pub fn cardinality_ratio(&self) -> f64 {
         if self.stats.total_count == 0 {
             0.0
         } else {
-            self.unique_values.len() as f64
-                / (self.stats.total_count - self.stats.null_count) as f64
+            self.distinct.count() as f64 / (self.stats.total_count - self.stats.null_count) as f64
         }
     }
 
-    pub fn is_likely_categorical(&self) -> bool {
+    /// Whether the column holds a few values that repeat: at most `max_values`
+    /// distinct ones, each seen at least twice on average
+    pub fn is_likely_categorical(&self, max_values: usize) -> bool {
         let non_null_count = self.stats.total_count - self.stats.null_count;
+        let unique_count = self.distinct.count();
 
-        if non_null_count == 0 {
-            return false;
-        }
+        non_null_count > 0 && unique_count <= max_values && unique_count * 2 <= non_null_count
+    }
+}
 
-        // Consider it categorical if:
-        // 1. Low cardinality ratio (< 0.1) and reasonable number of values
-        // 2. Very few unique values (< 20) regardless of ratio
-        let cardinality = self.cardinality_ratio();
-        let unique_count = self.unique_values.len();
+fn quote_values(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|v| format!("'{}'", v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A plain decimal number without a `+` sign, leading integer zeros or
+/// trailing fractional zeros, e.g. `-0012.50` is `-12.5`
+fn canonical_number(value: &str) -> Cow<'_, str> {
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let int_part = int_part.trim_start_matches('0');
+    let frac_part = frac_part.trim_end_matches('0');
+    if int_part.is_empty() && frac_part.is_empty() {
+        return Cow::Borrowed("0");
+    }
 
-        (cardinality < 0.1 && non_null_count > 10) || unique_count < 20
+    let int_part = if int_part.is_empty() { "0" } else { int_part };
+    let sign = if negative { "-" } else { "" };
+    let canonical = match frac_part {
+        "" => format!("{}{}", sign, int_part),
+        _ => format!("{}{}.{}", sign, int_part, frac_part),
+    };
+    if canonical == value {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(canonical)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analyzer::lattice::PromotionRule;
     use crate::analyzer::patterns::TypeInferencer;
 
+    fn column() -> ColumnAnalyzer {
+        column_with(TypeInferencer::new())
+    }
+
+    fn column_with(inferencer: TypeInferencer) -> ColumnAnalyzer {
+        ColumnAnalyzer::new("c".to_string(), inferencer, vec![], false)
+    }
+
+    /// Feed the values in order as rows 1, 2, ... and finalize the column
+    fn analyze(mut analyzer: ColumnAnalyzer, values: &[&str]) -> ColumnStats {
+        for (row, value) in values.iter().enumerate() {
+            analyzer.analyze_value(value, row + 1);
+        }
+        analyzer.finalize();
+        analyzer.into_stats()
+    }
+
     #[test]
     fn test_basic_analysis() {
         let inferencer = TypeInferencer::new();
@@ -255,12 +943,300 @@ mod tests {
         let mut analyzer = ColumnAnalyzer::new("test_col".to_string(), inferencer, vec![], false);
 
         analyzer.analyze_value("123", 1); // SmallInt
-        analyzer.analyze_value("true", 2); // Boolean -> promotes to SmallInt
-        analyzer.analyze_value("2147483648", 3); // BigInt -> promotes to BigInt
+        analyzer.analyze_value("2147483648", 2); // BigInt -> promotes to BigInt
 
         let stats = analyzer.get_stats();
         assert_eq!(stats.sql_type, SqlType::BigInt);
-        assert!(!stats.type_promotions.is_empty());
+        assert!(!stats.promotions.is_empty());
+
+        // `true` is no integer, so only VARCHAR accepts every value
+        analyzer.analyze_value("true", 3);
+        assert_eq!(analyzer.get_stats().sql_type, SqlType::Varchar(Some(10)));
+    }
+
+    #[test]
+    fn test_candidate_types_accept_every_value() {
+        assert_eq!(
+            analyze(column(), &["yes", "5"]).sql_type,
+            SqlType::Varchar(Some(3))
+        );
+        assert_eq!(
+            analyze(column(), &["1", "0", "1"]).sql_type,
+            SqlType::Boolean
+        );
+        assert_eq!(
+            analyze(column(), &["2024-01-02", "2024-01-02 10:30:00"]).sql_type,
+            SqlType::DateTime
+        );
+
+        let stats = analyze(column(), &["1", "true"]);
+        assert_eq!(stats.sql_type, SqlType::Boolean);
+        assert_eq!(
+            stats.notes,
+            vec!["Row 2: kept BOOLEAN for value: 'true' (rejects TINYINT, SMALLINT, INTEGER, BIGINT, DECIMAL, DOUBLE PRECISION)"]
+        );
+
+        let stats = analyze(column(), &["1", "7"]);
+        assert_eq!(stats.sql_type, SqlType::SmallInt);
+        assert_eq!(
+            stats
+                .promotions
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["Row 2: promoted from BOOLEAN to SMALLINT due to value: '7' (rule BOOLEAN + SMALLINT -> SMALLINT; rejects BOOLEAN)"]
+        );
+    }
+
+    #[test]
+    fn test_decimal_digit_tracking() {
+        let inferencer = TypeInferencer::new().with_exact_decimals(true);
+        let mut analyzer = ColumnAnalyzer::new("price".to_string(), inferencer, vec![], false);
+
+        for (row, value) in ["12.5", "7", "-1040.125"].iter().enumerate() {
+            analyzer.analyze_value(value, row + 1);
+        }
+        assert_eq!(
+            analyzer.get_stats().sql_type,
+            SqlType::Decimal {
+                precision: 7,
+                scale: 3
+            }
+        );
+
+        analyzer.analyze_value("1e3", 4);
+        assert_eq!(analyzer.get_stats().sql_type, SqlType::DoublePrecision);
+    }
+
+    #[test]
+    fn test_identifier_columns_kept_as_text() {
+        let analyze = |policy: NumericIdPolicy, values: &[&str]| {
+            analyze(
+                column_with(TypeInferencer::new().with_numeric_ids(policy)),
+                values,
+            )
+        };
+
+        let stats = analyze(NumericIdPolicy::LeadingZeros, &["90210", "02134", "10001"]);
+        assert_eq!(stats.sql_type, SqlType::Varchar(Some(5)));
+        assert_eq!(
+            stats.notes.last().unwrap(),
+            "Kept VARCHAR(5) for identifier-like values: '02134' (a number would drop their leading zeros or + sign)"
+        );
+
+        let stats = analyze(NumericIdPolicy::Never, &["90210", "02134"]);
+        assert_eq!(stats.sql_type, SqlType::Integer);
+
+        // Same width but no leading zeros: text only under the fixed-width policy
+        let zips = ["90210", "10001", "60601"];
+        assert_eq!(
+            analyze(NumericIdPolicy::LeadingZeros, &zips).sql_type,
+            SqlType::Integer
+        );
+        let stats = analyze(NumericIdPolicy::FixedWidth, &zips);
+        assert_eq!(stats.sql_type, SqlType::Varchar(Some(5)));
+        assert!(stats
+            .notes
+            .last()
+            .unwrap()
+            .contains("every value has 5 characters"));
+        assert_eq!(
+            analyze(NumericIdPolicy::FixedWidth, &["7", "42", "100"]).sql_type,
+            SqlType::SmallInt
+        );
+    }
+
+    #[test]
+    fn test_zoned_timestamps() {
+        let stats = analyze(
+            column(),
+            &["2024-03-01T10:00:00Z", "Fri, 01 Mar 2024 10:00:00 +0200"],
+        );
+        assert_eq!(stats.sql_type, SqlType::DateTimeTz);
+        assert!(stats.notes.is_empty());
+
+        assert_eq!(
+            analyze(column(), &["2024-03-01T10:00:00", "2024-03-01 11:00:00"]).sql_type,
+            SqlType::DateTime
+        );
+
+        let stats = analyze(
+            column(),
+            &["2024-03-01 10:00:00", "2024-03-01T10:00:00+02:00"],
+        );
+        assert_eq!(stats.sql_type, SqlType::DateTimeTz);
+        assert_eq!(
+            stats.notes.last().unwrap(),
+            "Mixes 1 zoned and 1 naive timestamps; naive values have no offset and are taken as UTC"
+        );
+    }
+
+    #[test]
+    fn test_fractional_second_precision() {
+        let stats = analyze(column(), &["12:00:00", "12:00:00.123456", "08:30:00.5"]);
+        assert_eq!(stats.sql_type, SqlType::Time);
+        assert_eq!(stats.time_precision, Some(6));
+
+        let stats = analyze(
+            column(),
+            &["2024-03-01 10:00:00.250", "2024-03-01 10:00:00"],
+        );
+        assert_eq!(stats.sql_type, SqlType::DateTime);
+        assert_eq!(stats.time_precision, Some(3));
+
+        assert_eq!(
+            analyze(column(), &["10:00:00", "11:00:00"]).time_precision,
+            None
+        );
+        assert_eq!(
+            analyze(column(), &["12:00:00.5", "abc"]).time_precision,
+            None
+        );
+    }
+
+    #[test]
+    fn test_date_format_discovery() {
+        // A day above 12 settles dd/mm
+        let stats = analyze(column(), &["03/04/2024", "25/04/2024"]);
+        assert_eq!(stats.sql_type, SqlType::Date);
+        assert_eq!(stats.date_format.as_deref(), Some("%d/%m/%Y"));
+        assert!(stats.notes.is_empty());
+
+        let stats = analyze(column(), &["03/04/2024", "05/06/2024"]);
+        assert_eq!(stats.date_format.as_deref(), Some("%m/%d/%Y"));
+        assert_eq!(
+            stats.notes,
+            vec!["Assumed date format %m/%d/%Y; every value also fits %d/%m/%Y"]
+        );
+
+        let stats = analyze(column(), &["2024-04-03", "03-Apr-2024"]);
+        assert_eq!(stats.sql_type, SqlType::Varchar(Some(11)));
+        assert_eq!(stats.date_format, None);
+
+        assert_eq!(
+            analyze(column(), &["2024-04-03"]).date_format.as_deref(),
+            Some("%Y-%m-%d")
+        );
+    }
+
+    #[test]
+    fn test_column_overrides() {
+        let analyze = |forced: ColumnOverride, values: &[&str]| {
+            analyze(column().with_override(forced), values)
+        };
+
+        let text = ColumnOverride {
+            sql_type: Some(SqlType::Varchar(Some(10))),
+            ..ColumnOverride::default()
+        };
+        let stats = analyze(text.clone(), &["200", "404"]);
+        assert_eq!(stats.sql_type, SqlType::Varchar(Some(10)));
+        assert!(stats.notes.iter().all(|m| !m.contains("conflict")));
+
+        let stats = analyze(text, &["200", "a much longer status"]);
+        assert_eq!(
+            stats.notes.last().unwrap(),
+            "Override conflict: is forced to VARCHAR(10), but values are up to 20 characters long"
+        );
+
+        let flag = ColumnOverride {
+            sql_type: Some(SqlType::SmallInt),
+            not_null: Some(true),
+            null_values: vec!["-".to_string()],
+            ..ColumnOverride::default()
+        };
+        let stats = analyze(flag, &["1", "0", "-", "yes"]);
+        assert_eq!(stats.sql_type, SqlType::SmallInt);
+        assert_eq!(stats.null_count, 1);
+        assert!(!stats.is_nullable());
+        assert_eq!(
+            stats.notes[stats.notes.len() - 2..],
+            [
+                "Override conflict: is forced to SMALLINT, but row 4 has 'yes'",
+                "Override conflict: is forced NOT NULL, but has 1 null values",
+            ]
+        );
+
+        let day_first = ColumnOverride {
+            date_format: Some("%d/%m/%Y".to_string()),
+            ..ColumnOverride::default()
+        };
+        let stats = analyze(day_first, &["03/04/2024", "05/06/2024"]);
+        assert_eq!(stats.sql_type, SqlType::Date);
+        assert_eq!(stats.date_format.as_deref(), Some("%d/%m/%Y"));
+        assert!(stats.notes.is_empty());
+    }
+
+    #[test]
+    fn test_integers_beyond_bigint() {
+        let ids = ["42", "123456789012345678901"];
+
+        let stats = analyze(column(), &ids);
+        assert_eq!(
+            stats.sql_type,
+            SqlType::Decimal {
+                precision: 21,
+                scale: 0
+            }
+        );
+
+        let stats = analyze(
+            column_with(TypeInferencer::new().with_max_decimal_digits(20)),
+            &ids,
+        );
+        assert_eq!(stats.sql_type, SqlType::Varchar(Some(21)));
+        assert!(stats.promotions[0]
+            .to_string()
+            .contains("needs 21 digits, more than the DECIMAL maximum of 20"));
+
+        // PostgreSQL's NUMERIC holds up to 1000 digits
+        let nines = "9".repeat(300);
+        let stats = analyze(
+            column_with(TypeInferencer::new().with_max_decimal_digits(1000)),
+            &[&nines],
+        );
+        assert_eq!(
+            stats.sql_type,
+            SqlType::Decimal {
+                precision: 300,
+                scale: 0
+            }
+        );
+
+        // Each value fits 38 digits, but together they need DECIMAL(40,6)
+        let stats = analyze(
+            column_with(TypeInferencer::new().with_exact_decimals(true)),
+            &["1234567890123456789012345678901234.5", "0.123456"],
+        );
+        assert_eq!(stats.sql_type, SqlType::Varchar(Some(36)));
+        assert!(stats.promotions[0]
+            .to_string().contains(
+            "the column needs 40 digits (34 integer, 6 fractional), more than the DECIMAL maximum of 38"
+        ));
+    }
+
+    #[test]
+    fn test_dialect_promotion_rules() {
+        let lattice = PromotionLattice::default()
+            .with_rules(vec![PromotionRule::new("BOOLEAN", "SMALLINT", "TINYINT")])
+            .unwrap();
+        let analyze = |values: &[&str]| analyze(column().with_lattice(lattice.clone()), values);
+
+        assert_eq!(analyze(&["1", "5", "0", "7"]).sql_type, SqlType::TinyInt);
+        assert_eq!(analyze(&["5", "1"]).sql_type, SqlType::TinyInt);
+
+        // TINYINT cannot hold 1000, so the rule is not applied
+        let stats = analyze(&["1", "1000"]);
+        assert_eq!(stats.sql_type, SqlType::SmallInt);
+        assert!(stats.promotions[0]
+            .to_string()
+            .contains("does not fit every value"));
+
+        assert_eq!(analyze(&["1", "5", "70000"]).sql_type, SqlType::Integer);
+        assert_eq!(
+            analyze(&["10:00:00", "2024-01-02 10:00:00"]).sql_type,
+            SqlType::Varchar(Some(19))
+        );
     }
 
     #[test]
@@ -286,6 +1262,138 @@ mod tests {
         assert_eq!(stats.null_percentage(), 60.0);
     }
 
+    #[test]
+    fn test_type_evidence() {
+        let mut analyzer =
+            ColumnAnalyzer::new("amount".to_string(), TypeInferencer::new(), vec![], false);
+        for (i, value) in ["10", "250", "n/a", "37", "1.5", "n/a", "12"]
+            .iter()
+            .enumerate()
+        {
+            analyzer.analyze_value(value, i + 1);
+        }
+        analyzer.finalize();
+        let stats = analyzer.into_stats();
+
+        let evidence: Vec<(String, usize, usize)> = stats
+            .type_evidence
+            .iter()
+            .map(|e| (e.sql_type.to_string(), e.count, e.first_row))
+            .collect();
+        assert_eq!(
+            evidence,
+            vec![
+                ("SMALLINT".to_string(), 4, 1),
+                ("DOUBLE PRECISION".to_string(), 1, 5),
+                ("VARCHAR(3)".to_string(), 2, 3),
+            ]
+        );
+        assert_eq!(stats.type_evidence[0].examples, vec!["10", "250", "37"]);
+        assert_eq!(stats.type_evidence[2].examples, vec!["n/a"]);
+
+        // Only the change of type is a promotion; the DOUBLE is already text
+        assert_eq!(stats.promotions.len(), 1);
+        let promotion = &stats.promotions[0];
+        assert_eq!((promotion.row, promotion.value.as_str()), (3, "n/a"));
+        assert_eq!(promotion.from, SqlType::SmallInt);
+        assert_eq!(promotion.to, SqlType::Varchar(Some(3)));
+    }
+
+    #[test]
+    fn test_min_max_in_type_domain() {
+        let analyze = |values: &[&str]| {
+            let stats = analyze(column(), values);
+            (stats.min_value.unwrap(), stats.max_value.unwrap())
+        };
+
+        assert_eq!(analyze(&["9", "10", "-3"]), ("-3".into(), "10".into()));
+        assert_eq!(
+            analyze(&["31/12/2023", "02/01/2024", "15/06/2023"]),
+            ("15/06/2023".into(), "02/01/2024".into())
+        );
+        assert_eq!(
+            analyze(&["09:30:00", "17:05:00", "08:00:00"]),
+            ("08:00:00".into(), "17:05:00".into())
+        );
+
+        // Bare dates among timestamps count as midnight
+        assert_eq!(
+            analyze(&["2024-01-01", "2024-01-01 10:00:00", "2024-01-02"]),
+            ("2024-01-01".into(), "2024-01-02".into())
+        );
+
+        // Once promoted to text, the shortest and longest values
+        assert_eq!(
+            analyze(&["9", "10", "abc", "1234"]),
+            ("9".into(), "1234".into())
+        );
+    }
+
+    #[test]
+    fn test_null_semantics() {
+        let column = |nulls: &[&str]| {
+            let nulls = nulls.iter().map(|v| v.to_string()).collect();
+            ColumnAnalyzer::new("c".to_string(), TypeInferencer::new(), nulls, false)
+        };
+        let values = ["a", "", "  ", "NULL", "N/A"];
+
+        // Defaults: blanks trim to '', which is NULL; N/A is data but reported
+        let stats = analyze(column(&[]), &values);
+        assert_eq!(stats.null_count, 3);
+        assert_eq!(stats.null_like_values.get("N/A"), Some(&1));
+
+        // Only explicit tokens: the empty string is a value distinct from NULL
+        let stats = analyze(column(&["NULL"]).with_default_nulls(false), &values);
+        assert_eq!(stats.null_count, 1);
+        assert_eq!(stats.null_like_values.get(""), Some(&2));
+        assert_eq!(stats.min_value.as_deref(), Some(""));
+
+        let stats = analyze(
+            column(&["NULL"])
+                .with_default_nulls(false)
+                .with_blank_values(BlankPolicy::Null),
+            &values,
+        );
+        assert_eq!(stats.null_count, 2);
+
+        let stats = analyze(column(&[]).with_blank_values(BlankPolicy::Value), &values);
+        assert_eq!(stats.null_count, 2);
+        assert_eq!(stats.null_like_values.get("  "), Some(&1));
+        assert!(stats
+            .notes
+            .iter()
+            .any(|m| m == "Analyzed null-like values as data: '  ' (1), 'N/A' (1)"));
+
+        // A column that does not inherit the global tokens keeps only its own
+        let forced = ColumnOverride {
+            null_values: vec!["-".to_string()],
+            inherit_nulls: Some(false),
+            ..ColumnOverride::default()
+        };
+        let stats = analyze(
+            column(&["N/A"]).with_override(forced),
+            &["", "-", "N/A", "1"],
+        );
+        assert_eq!(stats.null_count, 1);
+        assert_eq!(stats.sql_type, SqlType::Varchar(Some(3)));
+    }
+
+    #[test]
+    fn test_key_values() {
+        let analyzer = column();
+        let key = |value| analyzer.key_value(value).map(|v| v.into_owned());
+
+        assert_eq!(key("1.50"), Some("1.5".to_string()));
+        assert_eq!(key("+001.0"), Some("1".to_string()));
+        assert_eq!(key("-.50"), Some("-0.5".to_string()));
+        assert_eq!(key("-0.0"), Some("0".to_string()));
+        assert_eq!(key("2024-01-01T10:00:00"), key("2024-01-01 10:00:00"));
+        assert_eq!(key("2024-01-01T12:00:00+02:00"), key("2024-01-01 10:00:00"));
+        assert_eq!(key("2024-01-01"), key("2024-01-01 00:00:00"));
+        assert_eq!(key("abc"), Some("abc".to_string()));
+        assert_eq!(key("NULL"), None);
+    }
+
     #[test]
     fn test_varchar_sizing() {
         let inferencer = TypeInferencer::new();
@@ -319,7 +1427,8 @@ mod tests {
             row += 1;
         }
 
-        assert!(analyzer.is_likely_categorical());
+        assert!(analyzer.is_likely_categorical(20));
+        assert!(!analyzer.is_likely_categorical(2));
         assert_eq!(analyzer.unique_value_count(), 3);
         assert!(analyzer.cardinality_ratio() < 0.1);
     }
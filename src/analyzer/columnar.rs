@@ -0,0 +1,274 @@
+use crate::cli::InputFormat;
+use crate::types::{ColumnStats, SqlType};
+use anyhow::{bail, Context, Result};
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, RecordBatch};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_schema::{ArrowError, DataType, Field, SchemaRef, TimeUnit};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+type BatchIter = Box<dyn Iterator<Item = Result<RecordBatch, ArrowError>>>;
+
+/// Describe a Parquet or Arrow IPC file from its embedded schema.
+///
+/// Types, DECIMAL precision/scale, timestamp precision and nullability come from
+/// the schema. With `scan` the data is also read for null counts and the longest
+/// string of each text column, which sizes its VARCHAR.
+pub fn describe_columnar(path: &Path, format: InputFormat, scan: bool) -> Result<Vec<ColumnStats>> {
+    let (schema, row_count, batches) = match format {
+        InputFormat::Parquet => open_parquet(path)?,
+        InputFormat::Arrow => open_arrow(path)?,
+        _ => bail!("{:?} input has no embedded schema", format),
+    };
+
+    let mut columns: Vec<ColumnStats> = schema
+        .fields()
+        .iter()
+        .map(|f| column_from_field(f))
+        .collect();
+
+    match row_count {
+        Some(rows) if !scan => {
+            for column in &mut columns {
+                column.total_count = rows;
+            }
+        }
+        // Arrow IPC carries no row count, so its batches are always walked
+        _ => {
+            for batch in batches {
+                let batch = batch?;
+                for (column, array) in columns.iter_mut().zip(batch.columns()) {
+                    column.total_count += array.len();
+                    if scan {
+                        scan_array(column, array.as_ref())?;
+                    }
+                }
+            }
+        }
+    }
+
+    if scan {
+        for column in &mut columns {
+            size_varchar(column);
+        }
+    }
+
+    Ok(columns)
+}
+
+fn open_parquet(path: &Path) -> Result<(SchemaRef, Option<usize>, BatchIter)> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+        .with_context(|| format!("Failed to read Parquet metadata from {}", path.display()))?;
+
+    let schema = builder.schema().clone();
+    let rows = builder.metadata().file_metadata().num_rows().max(0) as usize;
+    Ok((schema, Some(rows), Box::new(builder.build()?)))
+}
+
+/// Open an Arrow IPC file (Feather v2), falling back to the streaming format
+fn open_arrow(path: &Path) -> Result<(SchemaRef, Option<usize>, BatchIter)> {
+    let open = || File::open(path).with_context(|| format!("Failed to open {}", path.display()));
+
+    if let Ok(reader) = FileReader::try_new(open()?, None) {
+        return Ok((reader.schema(), None, Box::new(reader)));
+    }
+
+    let reader = StreamReader::try_new(BufReader::new(open()?), None)
+        .with_context(|| format!("{} is not an Arrow IPC file or stream", path.display()))?;
+    Ok((reader.schema(), None, Box::new(reader)))
+}
+
+fn column_from_field(field: &Field) -> ColumnStats {
+    let (sql_type, time_precision) = sql_type_for(field.data_type());
+
+    let mut column = ColumnStats::new(field.name().clone());
+    column.sql_type = sql_type;
+    column.time_precision = time_precision;
+    column.nullable = Some(field.is_nullable());
+    column
+}
+
+/// Map an Arrow type to a `SqlType`, with fractional-seconds digits for times
+fn sql_type_for(data_type: &DataType) -> (SqlType, Option<u8>) {
+    let sql_type = match data_type {
+        DataType::Boolean => SqlType::Boolean,
+        DataType::Int8 => SqlType::TinyInt,
+        DataType::Int16 | DataType::UInt8 => SqlType::SmallInt,
+        DataType::Int32 | DataType::UInt16 => SqlType::Integer,
+        DataType::Int64 | DataType::UInt32 => SqlType::BigInt,
+        DataType::UInt64 => SqlType::Decimal {
+            precision: 20,
+            scale: 0,
+        },
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => SqlType::DoublePrecision,
+        DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+            decimal_type(*precision, *scale)
+        }
+        DataType::Date32 | DataType::Date64 => SqlType::Date,
+        DataType::Time32(unit) | DataType::Time64(unit) => {
+            return (SqlType::Time, Some(unit_digits(unit)))
+        }
+        DataType::Timestamp(unit, None) => return (SqlType::DateTime, Some(unit_digits(unit))),
+        DataType::Timestamp(unit, Some(_)) => {
+            return (SqlType::DateTimeTz, Some(unit_digits(unit)))
+        }
+        DataType::List(_)
+        | DataType::LargeList(_)
+        | DataType::FixedSizeList(_, _)
+        | DataType::Struct(_)
+        | DataType::Map(_, _) => SqlType::Json,
+        DataType::Dictionary(_, value_type) => return sql_type_for(value_type),
+        _ => SqlType::Varchar(None),
+    };
+    (sql_type, None)
+}
+
+/// A negative scale multiplies by a power of ten, so it needs extra integer digits
+fn decimal_type(precision: u8, scale: i8) -> SqlType {
+    if scale < 0 {
+        SqlType::Decimal {
+            precision: precision as u16 + scale.unsigned_abs() as u16,
+            scale: 0,
+        }
+    } else {
+        SqlType::Decimal {
+            precision: precision as u16,
+            scale: scale as u16,
+        }
+    }
+}
+
+fn unit_digits(unit: &TimeUnit) -> u8 {
+    match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 3,
+        TimeUnit::Microsecond => 6,
+        TimeUnit::Nanosecond => 9,
+    }
+}
+
+fn scan_array(column: &mut ColumnStats, array: &dyn Array) -> Result<()> {
+    column.null_count += array.null_count();
+
+    if !matches!(column.sql_type, SqlType::Varchar(_)) {
+        return Ok(());
+    }
+
+    let strings = arrow_cast::cast(array, &DataType::Utf8)?;
+    let longest = strings
+        .as_string::<i32>()
+        .iter()
+        .flatten()
+        .map(str::len)
+        .max();
+    column.max_length = column.max_length.max(longest.unwrap_or(0));
+    Ok(())
+}
+
+/// Size a scanned VARCHAR the same way text analysis does
+fn size_varchar(column: &mut ColumnStats) {
+    if let SqlType::Varchar(_) = column.sql_type {
+        column.sql_type = match column.max_length {
+            len if len > 4000 => SqlType::Varchar(None),
+            len => SqlType::Varchar(Some(len.max(1))),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Decimal128Array, Int32Array, StringArray, TimestampMillisecondArray};
+    use arrow_schema::Schema;
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+    use tempfile::NamedTempFile;
+
+    fn write_parquet() -> NamedTempFile {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("price", DataType::Decimal128(10, 2), true),
+            Field::new(
+                "seen",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                true,
+            ),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(
+                    Decimal128Array::from(vec![Some(1999), None, Some(5)])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ),
+                Arc::new(TimestampMillisecondArray::from(vec![
+                    Some(0),
+                    Some(1),
+                    None,
+                ])),
+                Arc::new(StringArray::from(vec![Some("Ada"), None, Some("Grace")])),
+            ],
+        )
+        .unwrap();
+
+        let file = NamedTempFile::new().unwrap();
+        let mut writer = ArrowWriter::try_new(file.reopen().unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_parquet_schema_mapping() {
+        let file = write_parquet();
+        let columns = describe_columnar(file.path(), InputFormat::Parquet, false).unwrap();
+
+        let types: Vec<_> = columns.iter().map(|c| c.sql_type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                SqlType::Integer,
+                SqlType::Decimal {
+                    precision: 10,
+                    scale: 2
+                },
+                SqlType::DateTime,
+                SqlType::Varchar(None),
+            ]
+        );
+        assert_eq!(columns[0].nullable, Some(false));
+        assert_eq!(columns[1].nullable, Some(true));
+        assert_eq!(columns[2].time_precision, Some(3));
+        assert!(columns
+            .iter()
+            .all(|c| c.total_count == 3 && c.null_count == 0));
+    }
+
+    #[test]
+    fn test_parquet_scan() {
+        let file = write_parquet();
+        let columns = describe_columnar(file.path(), InputFormat::Parquet, true).unwrap();
+
+        let nulls: Vec<_> = columns.iter().map(|c| c.null_count).collect();
+        assert_eq!(nulls, vec![0, 1, 1, 1]);
+        assert_eq!(columns[3].sql_type, SqlType::Varchar(Some(5)));
+    }
+
+    #[test]
+    fn test_negative_decimal_scale() {
+        assert_eq!(
+            decimal_type(5, -3),
+            SqlType::Decimal {
+                precision: 8,
+                scale: 0
+            }
+        );
+    }
+}
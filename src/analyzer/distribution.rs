@@ -0,0 +1,163 @@
+use crate::types::NumericSummary;
+
+/// How finely the digest keeps the distribution; it holds at most about this
+/// many centroids
+const COMPRESSION: f64 = 100.0;
+/// Values buffered before they are merged into the digest
+const BUFFERED: usize = 500;
+
+/// Mean, variance and quantiles of a stream of numbers in fixed memory.
+///
+/// The mean and variance use Welford's method; quantiles come from a merging
+/// t-digest, whose centroids are smallest near the tails so p95 and p99 stay
+/// close.
+#[derive(Debug, Clone, Default)]
+pub struct NumberDistribution {
+    count: usize,
+    mean: f64,
+    /// Sum of squared differences from the mean
+    m2: f64,
+    min: f64,
+    max: f64,
+    /// Mean and weight of each centroid, by mean
+    centroids: Vec<(f64, f64)>,
+    buffer: Vec<f64>,
+}
+
+impl NumberDistribution {
+    pub fn record(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+
+        self.buffer.push(value);
+        if self.buffer.len() == BUFFERED {
+            self.compress();
+        }
+    }
+
+    /// Merge the buffer into the centroids, joining neighbours while the
+    /// result spans at most one unit of the scale `k`
+    fn compress(&mut self) {
+        let mut points = std::mem::take(&mut self.centroids);
+        points.extend(self.buffer.drain(..).map(|value| (value, 1.0)));
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let total: f64 = points.iter().map(|(_, weight)| weight).sum();
+        let mut merged: Vec<(f64, f64)> = Vec::new();
+        let mut before = 0.0;
+        for (mean, weight) in points {
+            if let Some(last) = merged.last_mut() {
+                let joined = last.1 + weight;
+                if k((before + joined) / total) - k(before / total) <= 1.0 {
+                    last.0 += (mean - last.0) * weight / joined;
+                    last.1 = joined;
+                    continue;
+                }
+                before += last.1;
+            }
+            merged.push((mean, weight));
+        }
+        self.centroids = merged;
+    }
+
+    /// The value below which a fraction `q` of the numbers lie
+    fn quantile(&self, q: f64) -> f64 {
+        let mut digest = self.clone();
+        digest.compress();
+        let centroids = &digest.centroids;
+        let total = self.count as f64;
+        let target = q * total;
+
+        // Each centroid's mean sits at the middle of its weight
+        let mut before = 0.0;
+        let mut previous = (self.min, 0.0);
+        for &(mean, weight) in centroids {
+            let at = before + weight / 2.0;
+            if target < at {
+                let span = at - previous.1;
+                let t = if span > 0.0 {
+                    (target - previous.1) / span
+                } else {
+                    0.0
+                };
+                return previous.0 + (mean - previous.0) * t;
+            }
+            previous = (mean, at);
+            before += weight;
+        }
+        let span = total - previous.1;
+        let t = if span > 0.0 {
+            (target - previous.1) / span
+        } else {
+            1.0
+        };
+        previous.0 + (self.max - previous.0) * t
+    }
+
+    pub fn summary(&self) -> Option<NumericSummary> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(NumericSummary {
+            mean: self.mean,
+            std_dev: (self.count > 1).then(|| (self.m2 / (self.count - 1) as f64).sqrt()),
+            p50: self.quantile(0.5),
+            p95: self.quantile(0.95),
+            p99: self.quantile(0.99),
+        })
+    }
+}
+
+/// Scale on which every centroid may span one unit: steep near 0 and 1, so
+/// centroids there hold few values
+fn k(q: f64) -> f64 {
+    COMPRESSION / (2.0 * std::f64::consts::PI) * (2.0 * q.min(1.0) - 1.0).asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moments_and_quantiles() {
+        let mut small = NumberDistribution::default();
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            small.record(value);
+        }
+        let summary = small.summary().unwrap();
+        assert_eq!(summary.mean, 5.0);
+        assert!((summary.std_dev.unwrap() - 2.138).abs() < 0.001);
+
+        // 0..100000 in a scrambled order
+        let mut large = NumberDistribution::default();
+        for i in 0..100_000u64 {
+            large.record(((i * 7919) % 100_000) as f64);
+        }
+        let summary = large.summary().unwrap();
+        assert!((summary.mean - 49_999.5).abs() < 1e-6);
+        for (estimate, expected) in [
+            (summary.p50, 50_000.0),
+            (summary.p95, 95_000.0),
+            (summary.p99, 99_000.0),
+        ] {
+            assert!(
+                (estimate - expected).abs() < 500.0,
+                "{} vs {}",
+                estimate,
+                expected
+            );
+        }
+        assert!(large.centroids.len() <= COMPRESSION as usize);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::types::ValueCount;
+use std::collections::{BTreeSet, HashMap};
+
+/// Counters kept for each value asked for, so the top values survive columns
+/// with many distinct ones
+const COUNTERS_PER_VALUE: usize = 10;
+const MIN_COUNTERS: usize = 100;
+
+/// The most frequent values of a column in bounded memory (Space-Saving).
+///
+/// Once every counter is taken, a new value replaces the least counted one and
+/// inherits its count as a possible overcount. Counters are kept ordered by
+/// count, so finding the least counted one takes logarithmic time.
+#[derive(Debug, Clone)]
+pub struct TopValues {
+    top: usize,
+    capacity: usize,
+    counters: Vec<Counter>,
+    /// Index of each tracked value in `counters`
+    index: HashMap<String, usize>,
+    /// Count and index of every counter, least counted first
+    order: BTreeSet<(usize, usize)>,
+}
+
+#[derive(Debug, Clone)]
+struct Counter {
+    value: String,
+    count: usize,
+    /// Part of `count` that may belong to the values it replaced
+    error: usize,
+}
+
+impl TopValues {
+    pub fn new(top: usize) -> Self {
+        TopValues {
+            top,
+            capacity: (top * COUNTERS_PER_VALUE).max(MIN_COUNTERS),
+            counters: Vec::new(),
+            index: HashMap::new(),
+            order: BTreeSet::new(),
+        }
+    }
+
+    pub fn record(&mut self, value: &str) {
+        if let Some(&i) = self.index.get(value) {
+            let counter = &mut self.counters[i];
+            self.order.remove(&(counter.count, i));
+            counter.count += 1;
+            self.order.insert((counter.count, i));
+            return;
+        }
+        if self.counters.len() < self.capacity {
+            let i = self.counters.len();
+            self.counters.push(Counter {
+                value: value.to_string(),
+                count: 1,
+                error: 0,
+            });
+            self.index.insert(value.to_string(), i);
+            self.order.insert((1, i));
+            return;
+        }
+
+        let Some((min_count, i)) = self.order.pop_first() else {
+            return;
+        };
+        let counter = &mut self.counters[i];
+        self.index.remove(&counter.value);
+        *counter = Counter {
+            value: value.to_string(),
+            count: min_count + 1,
+            error: min_count,
+        };
+        self.index.insert(value.to_string(), i);
+        self.order.insert((min_count + 1, i));
+    }
+
+    /// The most frequent values by the count each is guaranteed to have,
+    /// highest first. Once counters were replaced, any untracked value may
+    /// have been seen as often as the least counted one, so values that are
+    /// not guaranteed more are left out.
+    pub fn values(&self) -> Vec<ValueCount> {
+        let floor = match self.order.first() {
+            Some(&(min_count, _)) if self.counters.iter().any(|c| c.error > 0) => min_count,
+            _ => 0,
+        };
+        let mut values: Vec<ValueCount> = self
+            .counters
+            .iter()
+            .filter(|counter| counter.count - counter.error > floor)
+            .map(|counter| ValueCount {
+                value: counter.value.clone(),
+                count: counter.count - counter.error,
+                error: counter.error,
+            })
+            .collect();
+        values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        values.truncate(self.top);
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heavy_hitters_survive() {
+        let mut top = TopValues::new(2);
+        for i in 0..10_000 {
+            match i % 4 {
+                0 => top.record("red"),
+                1 => top.record("blue"),
+                _ => top.record(&format!("id-{}", i)),
+            }
+        }
+
+        let values = top.values();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].value, "blue");
+        assert_eq!(values[1].value, "red");
+        // Counts are guaranteed, and at most the error below the true count
+        assert!(values[0].count <= 2500 && values[0].count + values[0].error >= 2500);
+
+        // No value stands out from the others: none is reported
+        let mut uniform = TopValues::new(2);
+        for i in 0..10_000 {
+            uniform.record(&format!("id-{}", i % 500));
+        }
+        assert!(uniform.values().is_empty());
+
+        let mut exact = TopValues::new(3);
+        for value in ["a", "b", "a", "c", "a", "b"] {
+            exact.record(value);
+        }
+        let values = exact.values();
+        assert_eq!((values[0].value.as_str(), values[0].count), ("a", 3));
+        assert_eq!(values[2].error, 0);
+    }
+}
@@ -0,0 +1,149 @@
+/// Fixed bins a histogram keeps once its values no longer fit the buffer
+const BINS: usize = 128;
+/// Values kept as they are before binning, so small columns get exact buckets
+const BUFFERED: usize = 1024;
+
+/// Equi-width histogram of positions on a line, in fixed memory.
+///
+/// The first values are buffered; after that they fall into `BINS` bins whose
+/// width doubles, toward the new value, whenever one falls outside them.
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    buffer: Vec<f64>,
+    origin: f64,
+    width: f64,
+    bins: Vec<usize>,
+}
+
+impl Histogram {
+    pub fn record(&mut self, at: f64) {
+        if !at.is_finite() {
+            return;
+        }
+        if !self.bins.is_empty() {
+            self.add(at);
+            return;
+        }
+        self.buffer.push(at);
+        if self.buffer.len() == BUFFERED {
+            let (low, high) = bounds(&self.buffer);
+            self.origin = low;
+            self.width = if high > low {
+                (high - low) / (BINS - 1) as f64
+            } else {
+                1.0
+            };
+            self.bins = vec![0; BINS];
+            for at in std::mem::take(&mut self.buffer) {
+                self.add(at);
+            }
+        }
+    }
+
+    fn add(&mut self, at: f64) {
+        loop {
+            let offset = ((at - self.origin) / self.width).floor();
+            if (0.0..BINS as f64).contains(&offset) {
+                self.bins[offset as usize] += 1;
+                return;
+            }
+            let merged: Vec<usize> = self.bins.chunks(2).map(|pair| pair.iter().sum()).collect();
+            self.bins = vec![0; BINS];
+            if offset < 0.0 {
+                self.origin -= self.width * BINS as f64;
+                self.bins[BINS / 2..].copy_from_slice(&merged);
+            } else {
+                self.bins[..BINS / 2].copy_from_slice(&merged);
+            }
+            self.width *= 2.0;
+        }
+    }
+
+    /// At most `count` buckets of equal width from the lowest to the highest
+    /// value, as `(low, high, values)`
+    pub fn buckets(&self, count: usize) -> Vec<(f64, f64, usize)> {
+        if count == 0 {
+            return Vec::new();
+        }
+        if self.bins.is_empty() {
+            return self.buffered_buckets(count);
+        }
+
+        let (Some(first), Some(last)) = (
+            self.bins.iter().position(|&n| n > 0),
+            self.bins.iter().rposition(|&n| n > 0),
+        ) else {
+            return Vec::new();
+        };
+        let group = (last - first + 1).div_ceil(count);
+        (first..=last)
+            .step_by(group)
+            .map(|start| {
+                let end = (start + group).min(BINS);
+                (
+                    self.origin + start as f64 * self.width,
+                    self.origin + end as f64 * self.width,
+                    self.bins[start..end].iter().sum(),
+                )
+            })
+            .collect()
+    }
+
+    fn buffered_buckets(&self, count: usize) -> Vec<(f64, f64, usize)> {
+        if self.buffer.is_empty() {
+            return Vec::new();
+        }
+        let (low, high) = bounds(&self.buffer);
+        if low == high {
+            return vec![(low, high, self.buffer.len())];
+        }
+
+        let width = (high - low) / count as f64;
+        let mut counts = vec![0; count];
+        for at in &self.buffer {
+            let index = ((at - low) / width).floor() as usize;
+            counts[index.min(count - 1)] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, n)| (low + i as f64 * width, low + (i + 1) as f64 * width, n))
+            .collect()
+    }
+}
+
+fn bounds(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), &at| {
+            (low.min(at), high.max(at))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets() {
+        let mut small = Histogram::default();
+        for at in [0.0, 1.0, 2.0, 9.0, 10.0] {
+            small.record(at);
+        }
+        let counts: Vec<usize> = small.buckets(5).iter().map(|b| b.2).collect();
+        assert_eq!(counts, vec![2, 1, 0, 0, 2]);
+        assert_eq!(small.buckets(5)[0].0, 0.0);
+
+        // Past the buffer, bins widen to take values on either side
+        let mut large = Histogram::default();
+        for i in 0..5000 {
+            large.record((i % 100) as f64);
+        }
+        large.record(-1000.0);
+        large.record(1000.0);
+        let buckets = large.buckets(10);
+        assert!(buckets.len() <= 10);
+        assert_eq!(buckets.iter().map(|b| b.2).sum::<usize>(), 5002);
+        assert!(buckets[0].0 <= -1000.0 && buckets[buckets.len() - 1].1 > 1000.0);
+    }
+}
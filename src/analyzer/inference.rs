@@ -1,9 +1,18 @@
-use crate::analyzer::{column::ColumnAnalyzer, patterns::TypeInferencer};
+use crate::analyzer::{
+    column::ColumnAnalyzer,
+    distinct::DEFAULT_EXACT_DISTINCT,
+    keys::KeyFinder,
+    lattice::PromotionLattice,
+    overrides::Overrides,
+    patterns::{CellHints, TypeInferencer},
+};
+use crate::cli::{BlankPolicy, NumericIdPolicy, Tolerance};
 use crate::parser::ParsedCsvReader;
-use crate::types::ColumnStats;
+use crate::types::{ColumnStats, KeyCandidate, SqlType};
 use anyhow::{Context, Result};
 use csv::ReaderBuilder;
 use log;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read, Write};
@@ -15,7 +24,21 @@ pub struct StreamingInferenceEngine {
     error_count: usize,
     max_errors: usize,
     inferencer: TypeInferencer,
+    /// Each column's workbook cell hint, by position
+    cell_hints: CellHints,
+    lattice: PromotionLattice,
+    overrides: Overrides,
     null_values: Vec<String>,
+    default_nulls: bool,
+    blank_values: BlankPolicy,
+    input_null: Option<&'static str>,
+    tolerance: Option<Tolerance>,
+    exact_distinct: usize,
+    top: Option<usize>,
+    max_categories: Option<usize>,
+    find_keys: bool,
+    key_finder: Option<KeyFinder>,
+    keys: Vec<KeyCandidate>,
     verbose: bool,
     sub_newline: String,
 }
@@ -39,12 +62,109 @@ impl StreamingInferenceEngine {
             error_count: 0,
             max_errors,
             inferencer,
+            cell_hints: Vec::new(),
+            lattice: PromotionLattice::default(),
+            overrides: Overrides::default(),
             null_values,
+            default_nulls: true,
+            blank_values: BlankPolicy::default(),
+            input_null: None,
+            tolerance: None,
+            exact_distinct: DEFAULT_EXACT_DISTINCT,
+            top: None,
+            max_categories: None,
+            find_keys: false,
+            key_finder: None,
+            keys: Vec::new(),
             verbose,
             sub_newline,
         }
     }
 
+    /// Infer fixed-point values as exact DECIMAL rather than DOUBLE PRECISION
+    pub fn with_exact_decimals(mut self, exact: bool) -> Self {
+        self.inferencer = self.inferencer.with_exact_decimals(exact);
+        self
+    }
+
+    /// Most DECIMAL digits the target dialect accepts
+    pub fn with_max_decimal_digits(mut self, digits: usize) -> Self {
+        self.inferencer = self.inferencer.with_max_decimal_digits(digits);
+        self
+    }
+
+    /// Which number-like identifiers are kept as text
+    pub fn with_numeric_ids(mut self, policy: NumericIdPolicy) -> Self {
+        self.inferencer = self.inferencer.with_numeric_ids(policy);
+        self
+    }
+
+    /// Whether `DEFAULT_NULL_VALUES` are NULL besides the given null tokens
+    pub fn with_default_nulls(mut self, default_nulls: bool) -> Self {
+        self.default_nulls = default_nulls;
+        self
+    }
+
+    /// How values made only of whitespace are read
+    pub fn with_blank_values(mut self, policy: BlankPolicy) -> Self {
+        self.blank_values = policy;
+        self
+    }
+
+    /// Keep a column's narrow type when at most `tolerance` values disagree
+    pub fn with_tolerance(mut self, tolerance: Option<Tolerance>) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Count up to `limit` distinct values per column exactly before estimating
+    pub fn with_exact_distinct(mut self, limit: usize) -> Self {
+        self.exact_distinct = limit;
+        self
+    }
+
+    /// Keep each column's `top` most frequent values and a histogram
+    pub fn with_top(mut self, top: Option<usize>) -> Self {
+        self.top = top;
+        self
+    }
+
+    /// The token the input format writes for its own NULL, such as `JSON_NULL`
+    pub fn with_input_null(mut self, token: Option<&'static str>) -> Self {
+        self.input_null = token;
+        self
+    }
+
+    /// Keep the values of text columns with at most `max` distinct ones
+    pub fn with_categories(mut self, max: Option<usize>) -> Self {
+        self.max_categories = max;
+        self
+    }
+
+    /// Per-column settings that win over inference
+    pub fn with_overrides(mut self, overrides: Overrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// Native cell types of workbook columns, by position
+    pub fn with_cell_hints(mut self, hints: CellHints) -> Self {
+        self.cell_hints = hints;
+        self
+    }
+
+    /// Look for columns, and pairs of columns, that could be keys
+    pub fn with_keys(mut self, find_keys: bool) -> Self {
+        self.find_keys = find_keys;
+        self
+    }
+
+    /// Join mixed column types with a dialect's promotion rules
+    pub fn with_lattice(mut self, lattice: PromotionLattice) -> Self {
+        self.lattice = lattice;
+        self
+    }
+
     pub fn analyze_csv_file(
         &mut self,
         file_path: &str,
@@ -86,14 +206,28 @@ impl StreamingInferenceEngine {
 
         // Initialize analyzers for each column
         for (i, header) in self.headers.iter().enumerate() {
-            let analyzer = ColumnAnalyzer::new(
+            let mut analyzer = ColumnAnalyzer::new(
                 header.clone(),
-                self.inferencer.clone(),
+                self.inferencer
+                    .clone()
+                    .with_cell_hint(self.cell_hints.get(i).copied().flatten()),
                 self.null_values.clone(),
                 self.verbose,
-            );
+            )
+            .with_default_nulls(self.default_nulls)
+            .with_blank_values(self.blank_values)
+            .with_input_null(self.input_null)
+            .with_tolerance(self.tolerance)
+            .with_exact_distinct(self.exact_distinct)
+            .with_top(self.top)
+            .with_categories(self.max_categories)
+            .with_lattice(self.lattice.clone());
+            if let Some(forced) = self.overrides.for_column(header) {
+                analyzer = analyzer.with_override(forced);
+            }
             self.analyzers.insert(i, analyzer);
         }
+        self.key_finder = self.find_keys.then(|| KeyFinder::new(self.headers.len()));
 
         // Process each record from the parsed reader
         for result in &mut parsed_reader {
@@ -101,12 +235,12 @@ impl StreamingInferenceEngine {
                 Ok(record) => {
                     self.row_count += 1;
 
-                    if self.verbose && self.row_count % 10000 == 0 {
+                    if self.verbose && self.row_count.is_multiple_of(10000) {
                         eprintln!("Processed {} rows", self.row_count);
                     }
 
                     // Also log for RUST_LOG debug mode (but with lower frequency to avoid spam)
-                    if self.row_count % 10000 == 0 {
+                    if self.row_count.is_multiple_of(10000) {
                         log::debug!("Processed {} rows", self.row_count);
                     }
 
@@ -117,6 +251,7 @@ impl StreamingInferenceEngine {
                             analyzer.analyze_value(field, self.row_count);
                         }
                     }
+                    self.record_key_values(record.iter())?;
                 }
                 Err(e) => {
                     // Error already handled by ParsedCsvReader, just propagate
@@ -155,6 +290,7 @@ impl StreamingInferenceEngine {
                 stats.push(analyzer.into_stats());
             }
         }
+        self.finish_keys(&stats)?;
 
         Ok(stats)
     }
@@ -188,14 +324,28 @@ impl StreamingInferenceEngine {
 
         // Initialize analyzers for each column
         for (i, header) in self.headers.iter().enumerate() {
-            let analyzer = ColumnAnalyzer::new(
+            let mut analyzer = ColumnAnalyzer::new(
                 header.clone(),
-                self.inferencer.clone(),
+                self.inferencer
+                    .clone()
+                    .with_cell_hint(self.cell_hints.get(i).copied().flatten()),
                 self.null_values.clone(),
                 self.verbose,
-            );
+            )
+            .with_default_nulls(self.default_nulls)
+            .with_blank_values(self.blank_values)
+            .with_input_null(self.input_null)
+            .with_tolerance(self.tolerance)
+            .with_exact_distinct(self.exact_distinct)
+            .with_top(self.top)
+            .with_categories(self.max_categories)
+            .with_lattice(self.lattice.clone());
+            if let Some(forced) = self.overrides.for_column(header) {
+                analyzer = analyzer.with_override(forced);
+            }
             self.analyzers.insert(i, analyzer);
         }
+        self.key_finder = self.find_keys.then(|| KeyFinder::new(self.headers.len()));
 
         // Process each record
         for result in csv_reader.records() {
@@ -244,6 +394,7 @@ impl StreamingInferenceEngine {
                 stats.push(analyzer.into_stats());
             }
         }
+        self.finish_keys(&stats)?;
 
         Ok(stats)
     }
@@ -251,12 +402,12 @@ impl StreamingInferenceEngine {
     fn process_record(&mut self, record: &csv::StringRecord) -> Result<()> {
         self.row_count += 1;
 
-        if self.verbose && self.row_count % 10000 == 0 {
+        if self.verbose && self.row_count.is_multiple_of(10000) {
             eprintln!("Processed {} rows", self.row_count);
         }
 
         // Also log for RUST_LOG debug mode (but with lower frequency to avoid spam)
-        if self.row_count % 10000 == 0 {
+        if self.row_count.is_multiple_of(10000) {
             log::debug!("Processed {} rows", self.row_count);
         }
 
@@ -293,16 +444,68 @@ impl StreamingInferenceEngine {
         }
 
         // Process each field in the record (only if field count matches)
-        for (i, field) in record.iter().enumerate() {
+        let fields: Vec<String> = record
+            .iter()
+            .map(|field| field.replace('\n', &self.sub_newline).replace('\r', ""))
+            .collect();
+        for (i, field) in fields.iter().enumerate() {
             if let Some(analyzer) = self.analyzers.get_mut(&i) {
-                let processed_field = field.replace('\n', &self.sub_newline).replace('\r', "");
-                analyzer.analyze_value(&processed_field, self.row_count);
+                analyzer.analyze_value(field, self.row_count);
             }
         }
+        self.record_key_values(fields.iter().map(String::as_str))?;
+
+        Ok(())
+    }
+
+    /// Pass a row's values, as their columns read them, to the key finder
+    fn record_key_values<'a>(&mut self, fields: impl Iterator<Item = &'a str>) -> Result<()> {
+        if let Some(finder) = &mut self.key_finder {
+            let values: Vec<Option<Cow<str>>> = fields
+                .enumerate()
+                .map(|(i, field)| self.analyzers.get(&i).and_then(|a| a.key_value(field)))
+                .collect();
+            let values: Vec<Option<&str>> = values.iter().map(|v| v.as_deref()).collect();
+            finder.record(&values)?;
+        }
+        Ok(())
+    }
 
+    /// Settle the key candidates once every column has its type. Boolean,
+    /// floating point and JSON columns are not keys, nor is a lone row.
+    fn finish_keys(&mut self, stats: &[ColumnStats]) -> Result<()> {
+        let Some(finder) = self.key_finder.take() else {
+            return Ok(());
+        };
+        let eligible: Vec<bool> = stats
+            .iter()
+            .map(|stat| {
+                self.row_count > 1
+                    && stat.null_count == 0
+                    && !matches!(
+                        stat.sql_type,
+                        SqlType::Boolean | SqlType::DoublePrecision | SqlType::Json
+                    )
+            })
+            .collect();
+        self.keys = finder
+            .finish(&eligible)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, columns)| KeyCandidate {
+                columns: columns.iter().map(|&c| stats[c].name.clone()).collect(),
+                rows: self.row_count,
+                primary: i == 0,
+            })
+            .collect();
         Ok(())
     }
 
+    /// Columns that could be keys, the first one the primary key
+    pub fn key_candidates(&self) -> &[KeyCandidate] {
+        &self.keys
+    }
+
     pub fn get_summary(&self) -> InferenceSummary {
         InferenceSummary {
             total_rows: self.row_count,
@@ -312,18 +515,41 @@ impl StreamingInferenceEngine {
         }
     }
 
-    pub fn print_type_promotions(&self) {
+    /// Print how many values of each type every column had and why its type
+    /// changed, to stderr so it does not mix with `--json` or `--ddl` output
+    pub fn print_type_promotions(&self, stats: &[ColumnStats]) {
         if !self.verbose {
             return;
         }
 
-        for analyzer in self.analyzers.values() {
-            let stats = analyzer.get_stats();
-            if !stats.type_promotions.is_empty() {
-                println!("\nType promotions for column '{}':", stats.name);
-                for promotion in &stats.type_promotions {
-                    println!("  {}", promotion);
-                }
+        for stat in stats {
+            if stat.type_evidence.is_empty() && stat.promotions.is_empty() && stat.notes.is_empty()
+            {
+                continue;
+            }
+            eprintln!(
+                "\nType evidence for column '{}' ({}):",
+                stat.name, stat.sql_type
+            );
+            for evidence in &stat.type_evidence {
+                let examples: Vec<String> = evidence
+                    .examples
+                    .iter()
+                    .map(|v| format!("'{}'", v))
+                    .collect();
+                eprintln!(
+                    "  {:<20} {} values, first on row {}, e.g. {}",
+                    evidence.sql_type.to_string(),
+                    evidence.count,
+                    evidence.first_row,
+                    examples.join(", ")
+                );
+            }
+            for promotion in &stat.promotions {
+                eprintln!("  {}", promotion);
+            }
+            for note in &stat.notes {
+                eprintln!("  {}", note);
             }
         }
     }
@@ -414,7 +640,9 @@ mod tests {
 
         assert_eq!(stats.len(), 1);
         assert_eq!(stats[0].sql_type, crate::types::SqlType::Varchar(Some(6))); // max length from "456.78" is 6 chars
-        assert!(!stats[0].type_promotions.is_empty());
+        assert!(!stats[0].promotions.is_empty());
+        assert_eq!(stats[0].type_evidence.len(), 4);
+        assert_eq!(stats[0].promotions[0].value, "true");
     }
 
     #[test]
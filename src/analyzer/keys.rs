@@ -0,0 +1,300 @@
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+
+/// Columns whose pairs are also tried as keys; keys are usually among the first
+const PAIR_COLUMNS: usize = 16;
+/// Hashes a candidate sorts in memory before spilling them to disk as a run
+const RUN_LENGTH: usize = 1 << 15;
+/// Hashes read from each run at a time while merging
+const READ_AHEAD: usize = 1024;
+
+/// Finds the columns, and pairs of columns, whose values are present and
+/// distinct in every row.
+///
+/// Each candidate hashes its values per row. Hashes are sorted in runs of
+/// `RUN_LENGTH`, so a repeat within a run rules a candidate out early, and the
+/// runs are spilled to a temporary file and merged at the end. Two values
+/// sharing a 64-bit hash could hide a key, but never make one up.
+pub struct KeyFinder {
+    candidates: Vec<Candidate>,
+    spill: Option<BufWriter<File>>,
+    /// Bytes written to the spill file so far
+    spilled: u64,
+}
+
+struct Candidate {
+    columns: Vec<usize>,
+    /// Set once a NULL or a repeated value rules the candidate out
+    ruled_out: bool,
+    buffer: Vec<u64>,
+    /// Byte offset and length in hashes of each sorted run on disk
+    runs: Vec<(u64, usize)>,
+}
+
+impl Candidate {
+    fn new(columns: Vec<usize>) -> Self {
+        Candidate {
+            columns,
+            ruled_out: false,
+            buffer: Vec::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    fn rule_out(&mut self) {
+        self.ruled_out = true;
+        self.buffer = Vec::new();
+        self.runs.clear();
+    }
+
+    /// Sort the buffer, ruling the candidate out if it repeats a hash
+    fn sort_buffer(&mut self) -> bool {
+        self.buffer.sort_unstable();
+        if self.buffer.windows(2).any(|pair| pair[0] == pair[1]) {
+            self.rule_out();
+            return false;
+        }
+        true
+    }
+}
+
+impl KeyFinder {
+    pub fn new(columns: usize) -> Self {
+        let mut candidates: Vec<Candidate> =
+            (0..columns).map(|i| Candidate::new(vec![i])).collect();
+        for i in 0..columns.min(PAIR_COLUMNS) {
+            for j in i + 1..columns.min(PAIR_COLUMNS) {
+                candidates.push(Candidate::new(vec![i, j]));
+            }
+        }
+        KeyFinder {
+            candidates,
+            spill: None,
+            spilled: 0,
+        }
+    }
+
+    /// Record one row, `None` standing for a NULL
+    pub fn record(&mut self, values: &[Option<&str>]) -> Result<()> {
+        let hashes: Vec<Option<u64>> = values
+            .iter()
+            .map(|value| {
+                value.map(|v| {
+                    let mut hasher = DefaultHasher::new();
+                    v.hash(&mut hasher);
+                    hasher.finish()
+                })
+            })
+            .collect();
+
+        for candidate in &mut self.candidates {
+            if candidate.ruled_out {
+                continue;
+            }
+            let mut hasher = DefaultHasher::new();
+            for &column in &candidate.columns {
+                match hashes.get(column).copied().flatten() {
+                    Some(hash) => hash.hash(&mut hasher),
+                    None => {
+                        candidate.rule_out();
+                        break;
+                    }
+                }
+            }
+            if candidate.ruled_out {
+                continue;
+            }
+
+            let hash = if candidate.columns.len() == 1 {
+                hashes[candidate.columns[0]].unwrap_or_default()
+            } else {
+                hasher.finish()
+            };
+            candidate.buffer.push(hash);
+            if candidate.buffer.len() == RUN_LENGTH && candidate.sort_buffer() {
+                if self.spill.is_none() {
+                    self.spill = Some(BufWriter::new(tempfile::tempfile()?));
+                }
+                let spill = self.spill.as_mut().expect("spill file was just created");
+                for hash in &candidate.buffer {
+                    spill.write_all(&hash.to_le_bytes())?;
+                }
+                candidate.runs.push((self.spilled, candidate.buffer.len()));
+                self.spilled += 8 * candidate.buffer.len() as u64;
+                candidate.buffer.clear();
+            }
+        }
+        Ok(())
+    }
+
+    /// The minimal keys among the candidates whose columns are all
+    /// `eligible`: single columns, or else pairs of columns
+    pub fn finish(self, eligible: &[bool]) -> Result<Vec<Vec<usize>>> {
+        let mut file = match self.spill {
+            Some(spill) => Some(spill.into_inner().map_err(|e| e.into_error())?),
+            None => None,
+        };
+        let (singles, pairs): (Vec<Candidate>, Vec<Candidate>) = self
+            .candidates
+            .into_iter()
+            .filter(|candidate| {
+                !candidate.ruled_out
+                    && candidate
+                        .columns
+                        .iter()
+                        .all(|&c| eligible.get(c).copied().unwrap_or(false))
+            })
+            .partition(|candidate| candidate.columns.len() == 1);
+
+        let mut keys = Vec::new();
+        for candidates in [singles, pairs] {
+            // A pair holding a key column is not a minimal key
+            if !keys.is_empty() {
+                break;
+            }
+            for mut candidate in candidates {
+                if candidate.sort_buffer() && is_unique(&candidate, file.as_mut())? {
+                    keys.push(candidate.columns);
+                }
+            }
+        }
+        Ok(keys)
+    }
+}
+
+/// Whether the sorted buffer and runs of a candidate share no hash, merging
+/// the runs from the spill file
+fn is_unique(candidate: &Candidate, file: Option<&mut File>) -> Result<bool> {
+    let Some(file) = file.filter(|_| !candidate.runs.is_empty()) else {
+        // Nothing spilled: the sorted buffer was already checked
+        return Ok(true);
+    };
+
+    let mut runs: Vec<RunReader> = candidate
+        .runs
+        .iter()
+        .map(|&(offset, len)| RunReader {
+            offset,
+            remaining: len,
+            hashes: Vec::new(),
+            next: 0,
+        })
+        .collect();
+    let mut heap = BinaryHeap::new();
+    for (i, run) in runs.iter_mut().enumerate() {
+        if let Some(hash) = run.pop(file)? {
+            heap.push(Reverse((hash, i)));
+        }
+    }
+    let mut buffered = candidate.buffer.iter().copied().peekable();
+
+    let mut previous = None;
+    loop {
+        let from_runs = heap.peek().map(|Reverse((hash, _))| *hash);
+        let hash = match (from_runs, buffered.peek().copied()) {
+            (Some(run_hash), Some(buffer_hash)) if buffer_hash < run_hash => buffered.next(),
+            (Some(_), _) => {
+                let Reverse((hash, i)) = heap.pop().expect("heap was just peeked");
+                if let Some(next) = runs[i].pop(file)? {
+                    heap.push(Reverse((next, i)));
+                }
+                Some(hash)
+            }
+            (None, _) => buffered.next(),
+        };
+        let Some(hash) = hash else {
+            return Ok(true);
+        };
+        if previous == Some(hash) {
+            return Ok(false);
+        }
+        previous = Some(hash);
+    }
+}
+
+/// Reads one sorted run back from the spill file a block at a time
+struct RunReader {
+    offset: u64,
+    remaining: usize,
+    hashes: Vec<u64>,
+    next: usize,
+}
+
+impl RunReader {
+    fn pop(&mut self, file: &mut File) -> Result<Option<u64>> {
+        if self.next == self.hashes.len() {
+            if self.remaining == 0 {
+                return Ok(None);
+            }
+            let count = self.remaining.min(READ_AHEAD);
+            let mut bytes = vec![0u8; count * 8];
+            file.seek(SeekFrom::Start(self.offset))?;
+            file.read_exact(&mut bytes)?;
+            self.hashes = bytes
+                .chunks_exact(8)
+                .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("8-byte chunk")))
+                .collect();
+            self.next = 0;
+            self.offset += bytes.len() as u64;
+            self.remaining -= count;
+        }
+        self.next += 1;
+        Ok(Some(self.hashes[self.next - 1]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_keys(rows: &[Vec<Option<String>>], eligible: &[bool]) -> Vec<Vec<usize>> {
+        let mut finder = KeyFinder::new(eligible.len());
+        for row in rows {
+            let values: Vec<Option<&str>> = row.iter().map(|v| v.as_deref()).collect();
+            finder.record(&values).unwrap();
+        }
+        finder.finish(eligible).unwrap()
+    }
+
+    #[test]
+    fn test_keys_across_spilled_runs() {
+        // Enough rows to spill several runs; the last row repeats the first
+        // value of column 1, far outside any run it shares
+        let rows = 3 * RUN_LENGTH + 10;
+        let mut data: Vec<Vec<Option<String>>> = (0..rows)
+            .map(|i| {
+                vec![
+                    Some(i.to_string()),
+                    Some(format!("v{}", i)),
+                    Some((i % 7).to_string()),
+                    (i != 5).then(|| format!("n{}", i)),
+                ]
+            })
+            .collect();
+        data[rows - 1][1] = Some("v0".to_string());
+        assert_eq!(find_keys(&data, &[true; 4]), vec![vec![0]]);
+
+        // Only ineligible or repeated columns left: pairs are tried
+        assert_eq!(
+            find_keys(&data, &[false, true, true, true]),
+            vec![vec![1, 2]]
+        );
+    }
+
+    #[test]
+    fn test_pairs_only_without_single_keys() {
+        let data: Vec<Vec<Option<String>>> = [("a", "1"), ("a", "2"), ("b", "1")]
+            .iter()
+            .map(|(x, y)| vec![Some(x.to_string()), Some(y.to_string())])
+            .collect();
+        assert_eq!(find_keys(&data, &[true, true]), vec![vec![0, 1]]);
+
+        let mut repeated = data.clone();
+        repeated.push(vec![Some("a".to_string()), Some("1".to_string())]);
+        assert!(find_keys(&repeated, &[true, true]).is_empty());
+    }
+}
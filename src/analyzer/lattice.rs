@@ -0,0 +1,177 @@
+use crate::analyzer::type_set::TypeSet;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The type a column widens to once it has held values of both `from` types,
+/// e.g. `{"from": ["DATE", "DATETIME"], "to": "DATETIME"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromotionRule {
+    pub from: [String; 2],
+    pub to: String,
+}
+
+impl PromotionRule {
+    pub fn new(a: &str, b: &str, to: &str) -> Self {
+        PromotionRule {
+            from: [a.to_string(), b.to_string()],
+            to: to.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for PromotionRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} + {} -> {}", self.from[0], self.from[1], self.to)
+    }
+}
+
+/// Data-driven type promotion: the join of two different types is given by a
+/// rule for that pair, and is VARCHAR when no rule covers them.
+#[derive(Debug, Clone)]
+pub struct PromotionLattice {
+    rules: Vec<(TypeSet, TypeSet, PromotionRule)>,
+}
+
+/// Numeric types in widening order; any two join to the wider one
+const NUMERIC_CHAIN: [&str; 7] = [
+    "BOOLEAN",
+    "TINYINT",
+    "SMALLINT",
+    "INTEGER",
+    "BIGINT",
+    "DECIMAL",
+    "DOUBLE PRECISION",
+];
+
+impl Default for PromotionLattice {
+    fn default() -> Self {
+        let mut rules = Vec::new();
+        for (i, narrow) in NUMERIC_CHAIN.iter().enumerate() {
+            for wide in &NUMERIC_CHAIN[i + 1..] {
+                rules.push(PromotionRule::new(narrow, wide, wide));
+            }
+        }
+        rules.push(PromotionRule::new("DATE", "DATETIME", "DATETIME"));
+        rules.push(PromotionRule::new("DATETIME", "TIMESTAMPTZ", "TIMESTAMPTZ"));
+
+        PromotionLattice::new(rules).expect("default promotion rules are valid")
+    }
+}
+
+impl PromotionLattice {
+    pub fn new(rules: Vec<PromotionRule>) -> Result<Self> {
+        let mut lattice = PromotionLattice { rules: Vec::new() };
+        lattice.extend(rules)?;
+        Ok(lattice)
+    }
+
+    /// Add rules, replacing any existing rule for the same pair of types
+    pub fn with_rules(mut self, rules: Vec<PromotionRule>) -> Result<Self> {
+        self.extend(rules)?;
+        Ok(self)
+    }
+
+    fn extend(&mut self, rules: Vec<PromotionRule>) -> Result<()> {
+        for rule in rules {
+            let a = parse_kind(&rule.from[0])?;
+            let b = parse_kind(&rule.from[1])?;
+            let to = parse_kind(&rule.to)?;
+            if a == b {
+                bail!("Promotion rule '{}' must name two different types", rule);
+            }
+
+            let pair = a | b;
+            self.rules.retain(|(from, _, _)| *from != pair);
+            self.rules.push((pair, to, rule));
+        }
+        Ok(())
+    }
+
+    /// The type holding both `a` and `b`, with the rule that decided it
+    pub fn join(&self, a: TypeSet, b: TypeSet) -> (TypeSet, Option<&PromotionRule>) {
+        if a == b {
+            return (a, None);
+        }
+
+        self.rules
+            .iter()
+            .find(|(from, _, _)| *from == a | b)
+            .map_or((TypeSet::VARCHAR, None), |(_, to, rule)| (*to, Some(rule)))
+    }
+
+    /// Join every kind in `kinds`, narrowest first, with the last rule that applied
+    pub fn join_all(&self, kinds: TypeSet) -> (TypeSet, Option<&PromotionRule>) {
+        let mut iter = kinds.kinds();
+        let Some(first) = iter.next() else {
+            return (TypeSet::VARCHAR, None);
+        };
+
+        iter.fold((first, None), |(joined, last_rule), kind| {
+            match self.join(joined, kind) {
+                (next, _) if next == joined => (joined, last_rule),
+                changed => changed,
+            }
+        })
+    }
+}
+
+fn parse_kind(name: &str) -> Result<TypeSet> {
+    TypeSet::from_name(name).ok_or_else(|| anyhow!("Unknown type in promotion rule: {}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_lattice() {
+        let lattice = PromotionLattice::default();
+
+        let (joined, rule) = lattice.join(TypeSet::SMALLINT, TypeSet::BOOLEAN);
+        assert_eq!(joined, TypeSet::SMALLINT);
+        assert_eq!(rule.unwrap().to_string(), "BOOLEAN + SMALLINT -> SMALLINT");
+
+        assert_eq!(
+            lattice.join(TypeSet::DATETIME, TypeSet::DATE).0,
+            TypeSet::DATETIME
+        );
+        assert_eq!(
+            lattice.join(TypeSet::DATETIME, TypeSet::DATETIMETZ).0,
+            TypeSet::DATETIMETZ
+        );
+        assert_eq!(
+            lattice.join(TypeSet::TIME, TypeSet::DATETIME),
+            (TypeSet::VARCHAR, None)
+        );
+    }
+
+    #[test]
+    fn test_rule_overrides() {
+        let lattice = PromotionLattice::default()
+            .with_rules(vec![
+                PromotionRule::new("boolean", "smallint", "tinyint"),
+                PromotionRule::new("DATE", "TIMESTAMP", "VARCHAR"),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            lattice.join(TypeSet::BOOLEAN, TypeSet::SMALLINT).0,
+            TypeSet::TINYINT
+        );
+        assert_eq!(
+            lattice.join(TypeSet::DATE, TypeSet::DATETIME).0,
+            TypeSet::VARCHAR
+        );
+
+        // Folding keeps the rule result for later values of either type
+        let (joined, rule) = lattice.join_all(TypeSet::BOOLEAN | TypeSet::SMALLINT);
+        assert_eq!(joined, TypeSet::TINYINT);
+        assert_eq!(rule.unwrap().to_string(), "boolean + smallint -> tinyint");
+        let (joined, _) = lattice.join_all(TypeSet::BOOLEAN | TypeSet::SMALLINT | TypeSet::INTEGER);
+        assert_eq!(joined, TypeSet::INTEGER);
+
+        assert!(PromotionLattice::new(vec![PromotionRule::new("DATE", "DATE", "TIME")]).is_err());
+        assert!(PromotionLattice::new(vec![PromotionRule::new("DATE", "UUID", "TIME")]).is_err());
+    }
+}
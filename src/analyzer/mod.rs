@@ -1,18 +1,36 @@
 pub mod column;
+pub mod columnar;
 pub mod diagnose;
+pub mod distinct;
+pub mod distribution;
+pub mod frequency;
+pub mod histogram;
 pub mod inference;
+pub mod keys;
+pub mod lattice;
+pub mod nulls;
 pub mod optimized;
+pub mod overrides;
 pub mod patterns;
-
-use crate::cli::{DatabaseType, DescribeArgs, DiagnoseArgs, ParseArgs};
-use crate::database::{get_database_dialect, get_database_dialect_from_config, DatabaseDialect};
-use crate::parser::ParsedCsvReader;
-use crate::types::ColumnStats;
+pub mod range;
+pub mod sentinels;
+pub mod tolerance;
+pub mod type_set;
+
+use crate::cli::{
+    ConstraintKind, DatabaseType, DecimalMode, DescribeArgs, DiagnoseArgs, InputFormat,
+    OutputFormat, ParseArgs,
+};
+use crate::database::{resolve_dialect, DatabaseDialect, DatabaseFeature};
+use crate::parser::{CsvDialect, InputSource, ParsedCsvReader};
+use crate::types::{ColumnStats, KeyCandidate, TableSchema};
+use crate::utils::default_table_name;
 use anyhow::{Context, Result};
-use encoding_rs::Encoding;
 use inference::StreamingInferenceEngine;
 use log::{debug, info};
-use std::fs::File;
+use overrides::Overrides;
+use patterns::CellHints;
+use range::number_label;
 use std::io::Read;
 use std::path::Path;
 
@@ -22,44 +40,89 @@ pub fn describe_command(args: DescribeArgs) -> Result<()> {
         debug!("Arguments: {:?}", args);
     }
 
-    // Convert DescribeArgs to ParseArgs to leverage parse command logic
-    let parse_args = convert_describe_to_parse_args(&args);
+    let overrides = match &args.overrides {
+        Some(path) => Overrides::from_file(path)?,
+        None => Overrides::default(),
+    }
+    .with_null_values(&args.fnull_column);
+
+    // Parquet and Arrow IPC files carry their own schema; everything else is
+    // analyzed as delimited text
+    let format = InputFormat::resolve(args.input_format, args.input.as_deref());
+    let (mut stats, keys) = match format {
+        InputFormat::Parquet | InputFormat::Arrow => {
+            let path = args
+                .input
+                .as_deref()
+                .context("Parquet and Arrow IPC input must be read from a file, not stdin")?;
+            (
+                columnar::describe_columnar(path, format, args.scan)?,
+                Vec::new(),
+            )
+        }
+        _ => analyze_text_input(&args, overrides.clone())?,
+    };
+    for stat in &mut stats {
+        stat.rename = overrides.for_column(&stat.name).and_then(|o| o.rename);
+    }
 
-    // Prepare null values list - use provided fnull or defaults
-    let null_values = if args.fnull.is_empty() {
-        vec!["".to_string(), "NULL".to_string(), "null".to_string()]
+    // Display results
+    if args.ddl {
+        print_ddl_output(&stats, &keys, &args.database, args.input.as_deref(), &args)?;
+    } else if args.json {
+        print_json_output(stats, keys, args.input.as_deref())?;
     } else {
-        args.fnull.clone()
-    };
+        print_analysis_output(&stats, &keys, args.verbose)?;
+    }
+
+    Ok(())
+}
+
+/// Infer column types, and key candidates if asked for, by streaming delimited
+/// text through the parse pipeline
+fn analyze_text_input(
+    args: &DescribeArgs,
+    overrides: Overrides,
+) -> Result<(Vec<ColumnStats>, Vec<KeyCandidate>)> {
+    // Convert DescribeArgs to ParseArgs to leverage parse command logic
+    let parse_args = convert_describe_to_parse_args(args);
+
+    // Mixed column types are joined with the target dialect's promotion rules
+    let dialect = resolve_dialect(&args.database, args.database_config.as_deref())?;
 
     // Create inference engine
     let mut engine = StreamingInferenceEngine::new(
-        null_values,
+        args.fnull.clone(),
         args.fdate.clone(),
         args.ftime.clone(),
         args.fdatetime.clone(),
         0, // max errors - fail on first error like parse command
         args.verbose,
         args.sub_newline.clone(),
-    );
-
-    // Create input reader with encoding support (like parse command)
-    let input: Box<dyn Read> = match &args.input {
-        Some(path) => Box::new(File::open(path)?),
-        None => Box::new(std::io::stdin()),
-    };
-
-    // Handle encoding (same as parse command)
-    let encoding = Encoding::for_label(parse_args.encoding.as_bytes())
-        .with_context(|| format!("Unsupported encoding: {}", parse_args.encoding))?;
-
-    let reader: Box<dyn Read> = if encoding == encoding_rs::UTF_8 {
-        input
-    } else {
-        // For non-UTF8 encodings, we need to decode first
-        let decoded_reader = crate::parser::EncodingReader::new(input, encoding);
-        Box::new(decoded_reader)
-    };
+    )
+    .with_lattice(dialect.promotion_lattice())
+    .with_max_decimal_digits(dialect.max_decimal_precision())
+    .with_exact_decimals(args.decimal_mode == DecimalMode::Exact)
+    .with_numeric_ids(args.numeric_ids_as_text)
+    .with_default_nulls(!args.no_default_nulls)
+    .with_blank_values(args.blank_values)
+    .with_tolerance(args.tolerance)
+    .with_exact_distinct(args.exact_distinct)
+    .with_top(args.top)
+    .with_categories(
+        args.constraints
+            .contains(&ConstraintKind::Categorical)
+            .then_some(args.max_categories),
+    )
+    .with_keys(args.constraints.contains(&ConstraintKind::Keys))
+    .with_overrides(overrides);
+
+    // Create input reader with encoding, workbook and JSON Lines support (like parse command)
+    let source = InputSource::from_parse_args(&parse_args);
+    let (reader, hints) = source.open_with_hints()?;
+    engine = engine
+        .with_cell_hints(hints)
+        .with_input_null(source.null_token());
 
     // Create ParsedCsvReader that will apply all parse command transformations
     let parsed_reader = ParsedCsvReader::new(reader, parse_args)?;
@@ -69,18 +132,9 @@ pub fn describe_command(args: DescribeArgs) -> Result<()> {
 
     // Print type promotions if verbose
     if args.verbose {
-        engine.print_type_promotions();
-    }
-
-    // Display results
-    if args.ddl {
-        print_ddl_output(&stats, &args.database, args.input.as_deref(), &args)?;
-    } else {
-        print_analysis_output(&stats, args.verbose)?;
-    }
+        engine.print_type_promotions(&stats);
 
-    let summary = engine.get_summary();
-    if args.verbose {
+        let summary = engine.get_summary();
         info!(
             "Analysis summary: {} rows, {} columns, {:.1}% success rate",
             summary.total_rows,
@@ -89,7 +143,7 @@ pub fn describe_command(args: DescribeArgs) -> Result<()> {
         );
     }
 
-    Ok(())
+    Ok((stats, engine.key_candidates().to_vec()))
 }
 
 /// Convert DescribeArgs to ParseArgs to reuse parse command logic
@@ -100,7 +154,7 @@ fn convert_describe_to_parse_args(args: &DescribeArgs) -> ParseArgs {
         delimiter: args.delimiter,
         quote: args.quote,
         escquote: args.escquote,
-        fnull: args.fnull.clone(),
+        fnull: Vec::new(),             // the analyzers match null tokens themselves
         tnull: String::new(),          // describe analyzes original null values
         badfile: None,                 // describe doesn't write bad files
         badmax: "0".to_string(),       // describe fails on first error like original
@@ -109,16 +163,79 @@ fn convert_describe_to_parse_args(args: &DescribeArgs) -> ParseArgs {
         encoding: "utf-8".to_string(), // default encoding
         verbose: args.verbose,
         sub_newline: args.sub_newline.clone(),
+        format: OutputFormat::Csv,
+        table: None,
+        batch_size: 500,
+        database: args.database,
+        database_config: None,
+        schema: None,
+        row_group_size: 65536,
+        sheet: args.sheet.clone(),
+        range: args.range.clone(),
+        input_format: args.input_format,
+        flatten_separator: args.flatten_separator.clone(),
+        no_default_nulls: args.no_default_nulls,
+        fnull_column: args.fnull_column.clone(),
+        blank_values: args.blank_values,
     }
 }
 
-fn print_analysis_output(stats: &[ColumnStats], _verbose: bool) -> Result<()> {
+/// Infer a table schema from parse input, for output formats that need column
+/// types when no `--schema` is given. Bad rows are skipped here; they are
+/// reported by the pass that writes the output.
+pub fn infer_schema<R: Read>(input: R, args: &ParseArgs, hints: CellHints) -> Result<TableSchema> {
+    let dialect = resolve_dialect(&args.database, args.database_config.as_deref())?;
+    let mut engine = StreamingInferenceEngine::new(
+        args.fnull.clone(),
+        None,
+        None,
+        None,
+        0,
+        false,
+        args.sub_newline.clone(),
+    )
+    .with_default_nulls(!args.no_default_nulls)
+    .with_blank_values(args.blank_values)
+    .with_lattice(dialect.promotion_lattice())
+    .with_max_decimal_digits(dialect.max_decimal_precision())
+    .with_overrides(Overrides::default().with_null_values(&args.fnull_column))
+    .with_cell_hints(hints)
+    .with_input_null(InputSource::from_parse_args(args).null_token());
+
+    // Keep the original null tokens so the analyzers see them
+    let mut inference_args = args.clone();
+    inference_args.fnull = Vec::new();
+    inference_args.tnull = String::new();
+    let parsed_reader = ParsedCsvReader::new(input, inference_args)?.lenient();
+
+    let columns = engine.analyze_with_parsed_reader(parsed_reader)?;
+    Ok(TableSchema::new(
+        default_table_name(args.input.as_deref()),
+        columns,
+    ))
+}
+
+fn print_analysis_output(
+    stats: &[ColumnStats],
+    keys: &[KeyCandidate],
+    _verbose: bool,
+) -> Result<()> {
     // Print table header
     println!(
-        "{:<20} {:<15} {:<8} {:<8} {:<8} {:<10}",
-        "Column", "Type", "Nulls", "Total", "Null%", "Max Len"
+        "{:<20} {:<15} {:<8} {:<8} {:<8} {:<10} {:<6} {:<19} {:<19} {:<10} {:<10}",
+        "Column",
+        "Type",
+        "Nulls",
+        "Total",
+        "Null%",
+        "Distinct",
+        "Card.",
+        "Min",
+        "Max",
+        "Max Len",
+        "Format"
     );
-    println!("{}", "-".repeat(80));
+    println!("{}", "-".repeat(149));
 
     // Print each column
     for stat in stats {
@@ -127,99 +244,378 @@ fn print_analysis_output(stats: &[ColumnStats], _verbose: bool) -> Result<()> {
         } else {
             "0.0%".to_string()
         };
+        // Estimated counts are marked with a tilde
+        let distinct = match stat.distinct {
+            Some(d) if d.exact => d.count.to_string(),
+            Some(d) => format!("~{}", d.count),
+            None => String::new(),
+        };
+        let cardinality = stat
+            .cardinality_ratio()
+            .map_or(String::new(), |ratio| format!("{:.3}", ratio));
 
         println!(
-            "{:<20} {:<15} {:<8} {:<8} {:<8} {:<10}",
+            "{:<20} {:<15} {:<8} {:<8} {:<8} {:<10} {:<6} {:<19} {:<19} {:<10} {:<10}",
             truncate_string(&stat.name, 20),
             truncate_string(&stat.sql_type.to_string(), 15),
             stat.null_count,
             stat.total_count,
             null_pct,
-            stat.max_length
+            distinct,
+            cardinality,
+            truncate_string(stat.min_value.as_deref().unwrap_or(""), 19),
+            truncate_string(stat.max_value.as_deref().unwrap_or(""), 19),
+            stat.max_length,
+            stat.date_format.as_deref().unwrap_or("")
         );
     }
 
+    print_value_statistics(stats);
+
+    // Values that look missing but were not null tokens in this run
+    let null_like: Vec<&ColumnStats> = stats
+        .iter()
+        .filter(|stat| !stat.null_like_values.is_empty())
+        .collect();
+    if !null_like.is_empty() {
+        println!();
+        println!("Null-like values analyzed as data (see --fnull and --blank-values):");
+        for stat in null_like {
+            let values: Vec<String> = stat
+                .null_like_values
+                .iter()
+                .map(|(value, count)| format!("'{}' x{}", value, count))
+                .collect();
+            println!("  {}: {}", stat.name, values.join(", "));
+        }
+    }
+
+    print_value_distribution(stats);
+    print_key_candidates(keys);
+    print_sentinel_report(stats);
+    print_expected_rejects(stats);
+
     Ok(())
 }
 
+/// Character lengths of every column and the spread of numeric ones
+fn print_value_statistics(stats: &[ColumnStats]) {
+    println!();
+    println!(
+        "{:<20} {:<18} {:<12} {:<12} {:<12} {:<12} {:<12}",
+        "Column", "Chars min/avg/max", "Mean", "Std Dev", "p50", "p95", "p99"
+    );
+    println!("{}", "-".repeat(104));
+
+    for stat in stats {
+        let length = stat.length.map_or(String::new(), |l| {
+            format!("{}/{:.1}/{}", l.min, l.avg, l.max)
+        });
+        let number = |n: Option<f64>| truncate_string(&n.map_or(String::new(), number_label), 12);
+        let numeric = stat.numeric;
+        println!(
+            "{:<20} {:<18} {:<12} {:<12} {:<12} {:<12} {:<12}",
+            truncate_string(&stat.name, 20),
+            length,
+            number(numeric.map(|n| n.mean)),
+            number(numeric.and_then(|n| n.std_dev)),
+            number(numeric.map(|n| n.p50)),
+            number(numeric.map(|n| n.p95)),
+            number(numeric.map(|n| n.p99)),
+        );
+    }
+}
+
+/// Top values and histograms gathered with `--top`
+fn print_value_distribution(stats: &[ColumnStats]) {
+    const BAR_WIDTH: usize = 40;
+
+    let with_top: Vec<&ColumnStats> = stats
+        .iter()
+        .filter(|stat| !stat.top_values.is_empty())
+        .collect();
+    if !with_top.is_empty() {
+        println!();
+        println!("Top values:");
+        for stat in with_top {
+            // Counts that may be too low are marked with a tilde
+            let values: Vec<String> = stat
+                .top_values
+                .iter()
+                .map(|v| {
+                    let tilde = if v.error > 0 { "~" } else { "" };
+                    format!("'{}' x{}{}", v.value, tilde, v.count)
+                })
+                .collect();
+            println!("  {}: {}", stat.name, values.join(", "));
+        }
+    }
+
+    for stat in stats.iter().filter(|stat| !stat.histogram.is_empty()) {
+        println!();
+        println!("Histogram of {}:", stat.name);
+        let most = stat.histogram.iter().map(|b| b.count).max().unwrap_or(0);
+        let range_width = stat
+            .histogram
+            .iter()
+            .map(|b| b.low.len() + b.high.len() + 4)
+            .max()
+            .unwrap_or(0);
+        for bucket in &stat.histogram {
+            let bar = (bucket.count * BAR_WIDTH).div_ceil(most.max(1));
+            println!(
+                "  {:<width$} {:>8} {}",
+                format!("{} .. {}", bucket.low, bucket.high),
+                bucket.count,
+                "#".repeat(bar),
+                width = range_width
+            );
+        }
+    }
+}
+
+/// Columns present and distinct in every row, as `--constraints keys` emits them
+fn print_key_candidates(keys: &[KeyCandidate]) {
+    if keys.is_empty() {
+        return;
+    }
+    println!();
+    println!("Key candidates:");
+    for key in keys {
+        println!(
+            "  {}: no NULLs and no repeats in {} rows ({})",
+            key.columns.join(", "),
+            key.rows,
+            if key.primary { "PRIMARY KEY" } else { "UNIQUE" }
+        );
+    }
+}
+
+/// Values under `--tolerance` that the chosen column types will reject
+fn print_expected_rejects(stats: &[ColumnStats]) {
+    const MAX_LISTED: usize = 10;
+
+    let rejecting: Vec<&ColumnStats> = stats
+        .iter()
+        .filter(|stat| !stat.expected_rejects.is_empty())
+        .collect();
+    if rejecting.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("Expected rejects within --tolerance:");
+    for stat in rejecting {
+        for reject in stat.expected_rejects.iter().take(MAX_LISTED) {
+            println!("  line {}, {}: '{}'", reject.line, stat.name, reject.value);
+        }
+        if stat.expected_rejects.len() > MAX_LISTED {
+            println!(
+                "  ... {} more in {} (see --json)",
+                stat.expected_rejects.len() - MAX_LISTED,
+                stat.name
+            );
+        }
+    }
+}
+
+/// List likely NULL sentinels, the types the columns would get without them,
+/// and the `--fnull` arguments that treat them as NULL
+fn print_sentinel_report(stats: &[ColumnStats]) {
+    let flagged: Vec<&ColumnStats> = stats.iter().filter(|s| !s.sentinels.is_empty()).collect();
+    if flagged.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("Possible null sentinels:");
+    // Per column, as a token such as -999 may be a real value elsewhere
+    let mut args: Vec<String> = Vec::new();
+    for stat in flagged {
+        for sentinel in &stat.sentinels {
+            println!(
+                "  {}: '{}' x{}, {}",
+                stat.name, sentinel.value, sentinel.count, sentinel.reason
+            );
+            let token = format!("{}={}", stat.name, sentinel.value);
+            args.push(format!("--fnull-column {}", shell_quote(&token)));
+        }
+        match &stat.type_without_sentinels {
+            Some(without) if *without != stat.sql_type => {
+                println!("    as NULL: {} instead of {}", without, stat.sql_type)
+            }
+            Some(without) => println!("    as NULL: still {}", without),
+            None => println!("    as NULL: every value would be NULL"),
+        }
+    }
+
+    println!("Suggested: {}", args.join(" "));
+}
+
+/// Quote a value for a POSIX shell unless it is plainly safe
+fn shell_quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:+=".contains(c));
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
 fn print_ddl_output(
     stats: &[ColumnStats],
+    keys: &[KeyCandidate],
     database: &DatabaseType,
     input_path: Option<&Path>,
     args: &DescribeArgs,
 ) -> Result<()> {
     // Generate table name from input file or use default
-    let table_name = if let Some(path) = input_path {
-        path.file_stem()
-            .and_then(|name| name.to_str())
-            .unwrap_or("imported_table")
-            .replace(" ", "_")
-            .replace("-", "_")
-    } else {
-        "imported_table".to_string()
-    };
+    let table_name = default_table_name(input_path);
 
     // Print CREATE TABLE statement
-    let dialect: Box<dyn DatabaseDialect> = if let Some(config_path) = &args.database_config {
-        if args.verbose {
-            info!("Using custom database configuration from: {:?}", config_path);
-        }
-        get_database_dialect_from_config(config_path)?
-    } else {
-        match database {
-            DatabaseType::Postgres => get_database_dialect("postgresql")?,
-            DatabaseType::Mysql => get_database_dialect("mysql")?,
-            DatabaseType::Netezza => get_database_dialect("netezza")?,
+    if args.verbose {
+        if let Some(config_path) = &args.database_config {
+            info!(
+                "Using custom database configuration from: {:?}",
+                config_path
+            );
         }
-    };
-    
-    print_ddl(&table_name, stats, dialect.as_ref())?;
+    }
+    let dialect = resolve_dialect(database, args.database_config.as_deref())?;
+
+    if args.constraints.contains(&ConstraintKind::Keys) && keys.is_empty() {
+        eprintln!(
+            "Warning: no column or pair of columns is present and distinct in every row; no PRIMARY KEY added"
+        );
+    }
+
+    print_ddl(&table_name, stats, keys, dialect.as_ref(), args.enum_types)?;
 
     Ok(())
 }
 
-fn print_ddl(table_name: &str, stats: &[ColumnStats], dialect: &dyn DatabaseDialect) -> Result<()> {
-    println!("CREATE TABLE {} (", table_name);
+fn print_ddl(
+    table_name: &str,
+    stats: &[ColumnStats],
+    keys: &[KeyCandidate],
+    dialect: &dyn DatabaseDialect,
+    enum_types: bool,
+) -> Result<()> {
+    let column_name = |stat: &ColumnStats| stat.ddl_name();
+    let categories = |stat: &ColumnStats| {
+        let values: Vec<String> = stat
+            .categories
+            .iter()
+            .map(|v| dialect.quote_string(v))
+            .collect();
+        values.join(", ")
+    };
+    let enum_types = enum_types && dialect.supports_feature(DatabaseFeature::EnumTypes);
+    let enum_type_name = |stat: &ColumnStats| {
+        dialect.quote_identifier(&format!("{}_{}_enum", table_name, column_name(stat)))
+    };
+
+    // Enum types are declared before the table that uses them
+    let categorical: Vec<&ColumnStats> = stats
+        .iter()
+        .filter(|stat| !stat.categories.is_empty())
+        .collect();
+    if enum_types && !categorical.is_empty() {
+        for stat in &categorical {
+            println!(
+                "CREATE TYPE {} AS ENUM ({});",
+                enum_type_name(stat),
+                categories(stat)
+            );
+        }
+        println!();
+    }
+
+    println!("CREATE TABLE {} (", dialect.quote_identifier(table_name));
 
-    for (i, stat) in stats.iter().enumerate() {
-        let column_name = sanitize_column_name(&stat.name);
-        let data_type = stat.sql_type.to_ddl(dialect);
+    let mut lines = Vec::new();
+    for stat in stats {
+        let column_name = dialect.quote_identifier(&column_name(stat));
+        let data_type = dialect.column_type(stat);
+        if let Some(warning) = dialect.type_warning(&stat.sql_type) {
+            eprintln!(
+                "Warning: column '{}' is {}: {}",
+                stat.name, data_type, warning
+            );
+        }
+        if !stat.expected_rejects.is_empty() {
+            eprintln!(
+                "Warning: column '{}' is {} within the tolerance; {} values will be rejected",
+                stat.name,
+                data_type,
+                stat.expected_rejects.len()
+            );
+        }
+        let mut check = String::new();
+        let data_type = if stat.categories.is_empty() {
+            data_type
+        } else if enum_types {
+            enum_type_name(stat)
+        } else if dialect.supports_feature(DatabaseFeature::InlineEnum) {
+            format!("ENUM({})", categories(stat))
+        } else if dialect.supports_feature(DatabaseFeature::CheckConstraints) {
+            check = format!(" CHECK ({} IN ({}))", column_name, categories(stat));
+            data_type
+        } else {
+            eprintln!(
+                "Warning: column '{}' gets no categorical constraint: {} has neither CHECK constraints nor ENUM types",
+                stat.name,
+                dialect.name()
+            );
+            data_type
+        };
         let nullable = if stat.is_nullable() { "" } else { " NOT NULL" };
-        let comma = if i == stats.len() - 1 { "" } else { "," };
 
-        println!("    {} {}{}{}", column_name, data_type, nullable, comma);
+        lines.push(format!(
+            "{} {}{}{}",
+            column_name, data_type, nullable, check
+        ));
+    }
+
+    for key in keys {
+        let columns: Vec<String> = key
+            .columns
+            .iter()
+            .filter_map(|name| stats.iter().find(|stat| &stat.name == name))
+            .map(|stat| dialect.quote_identifier(&column_name(stat)))
+            .collect();
+        let constraint = if key.primary { "PRIMARY KEY" } else { "UNIQUE" };
+        lines.push(format!("{} ({})", constraint, columns.join(", ")));
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        let comma = if i == lines.len() - 1 { "" } else { "," };
+        println!("    {}{}", line, comma);
     }
 
     println!(");");
     Ok(())
 }
 
-fn sanitize_column_name(name: &str) -> String {
-    // Replace spaces and special characters with underscores
-    let sanitized = name
-        .chars()
-        .map(|c| {
-            if c.is_alphanumeric() || c == '_' {
-                c
-            } else {
-                '_'
-            }
-        })
-        .collect::<String>();
-
-    // Ensure it starts with a letter or underscore
-    if sanitized.chars().next().is_some_and(|c| c.is_ascii_digit()) {
-        format!("_{}", sanitized)
-    } else {
-        sanitized
-    }
+fn print_json_output(
+    stats: Vec<ColumnStats>,
+    keys: Vec<KeyCandidate>,
+    input_path: Option<&Path>,
+) -> Result<()> {
+    let schema = TableSchema::new(default_table_name(input_path), stats).with_keys(keys);
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
 }
 
 fn truncate_string(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
+    if s.chars().count() <= max_len {
         s.to_string()
     } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
+        let kept: String = s.chars().take(max_len.saturating_sub(3)).collect();
+        format!("{}...", kept)
     }
 }
 
@@ -229,23 +625,21 @@ pub fn diagnose_command(args: DiagnoseArgs) -> Result<()> {
         debug!("Arguments: {:?}", args);
     }
 
-    // Create input reader with encoding support
-    let input: Box<dyn Read> = match &args.input {
-        Some(path) => Box::new(File::open(path)?),
-        None => Box::new(std::io::stdin()),
-    };
-
-    // Handle encoding
-    let encoding = Encoding::for_label(args.encoding.as_bytes())
-        .with_context(|| format!("Unsupported encoding: {}", args.encoding))?;
-
-    let reader: Box<dyn Read> = if encoding == encoding_rs::UTF_8 {
-        input
-    } else {
-        // For non-UTF8 encodings, we need to decode first
-        let decoded_reader = crate::parser::EncodingReader::new(input, encoding);
-        Box::new(decoded_reader)
-    };
+    // Create input reader with encoding and workbook support
+    let reader = InputSource {
+        path: args.input.as_deref(),
+        format: InputFormat::resolve(None, args.input.as_deref()),
+        encoding: &args.encoding,
+        sheet: args.sheet.as_deref(),
+        range: args.range.as_deref(),
+        flatten_separator: "_",
+        dialect: CsvDialect {
+            delimiter: args.delimiter,
+            quote: args.quote,
+            escquote: args.escquote,
+        },
+    }
+    .open()?;
 
     // Run diagnosis
     let summary = diagnose::diagnose_csv(reader, &args)?;
@@ -0,0 +1,119 @@
+use crate::analyzer::overrides::{ColumnOverride, Overrides};
+use crate::cli::BlankPolicy;
+use std::collections::HashSet;
+
+/// Values treated as NULL unless default nulls are turned off
+pub const DEFAULT_NULL_VALUES: [&str; 3] = ["", "NULL", "null"];
+
+/// Undo the escape an input format gives literal values that would read as
+/// its NULL `token`: backslashes followed by the token carry one extra
+/// backslash, so `\\N` is the text `\N` when the token is `\N`
+pub fn unescape_input_null<'a>(token: &str, value: &'a str) -> &'a str {
+    match value.strip_suffix(token) {
+        Some(prefix) if !prefix.is_empty() && prefix.bytes().all(|b| b == b'\\') => &value[1..],
+        _ => value,
+    }
+}
+
+/// Which values are NULL: explicit tokens, the defaults unless turned off,
+/// blanks under `BlankPolicy::Null`, and per-column tokens. Describe and the
+/// typed parse formats both read values through it.
+#[derive(Debug, Clone)]
+pub struct NullPolicy {
+    /// Explicit null tokens
+    tokens: HashSet<String>,
+    /// Whether `DEFAULT_NULL_VALUES` are NULL too
+    default_nulls: bool,
+    blank_values: BlankPolicy,
+    /// Per-column rules, such as `--fnull-column` tokens
+    columns: Overrides,
+    /// The input format's own NULL, such as JSON null, whatever the other rules say
+    input_null: Option<&'static str>,
+}
+
+impl NullPolicy {
+    pub fn new(tokens: Vec<String>) -> Self {
+        NullPolicy {
+            tokens: tokens.into_iter().collect(),
+            default_nulls: true,
+            blank_values: BlankPolicy::default(),
+            columns: Overrides::default(),
+            input_null: None,
+        }
+    }
+
+    /// Whether `DEFAULT_NULL_VALUES` are NULL besides the explicit tokens
+    pub fn with_default_nulls(mut self, default_nulls: bool) -> Self {
+        self.default_nulls = default_nulls;
+        self
+    }
+
+    /// How values made only of whitespace are read
+    pub fn with_blank_values(mut self, policy: BlankPolicy) -> Self {
+        self.blank_values = policy;
+        self
+    }
+
+    /// Rules whose `null_values` and `inherit_nulls` apply to matching columns
+    pub fn with_columns(mut self, columns: Overrides) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// The token the input format writes for its own NULL, see `JSON_NULL`
+    pub fn with_input_null(mut self, token: Option<&'static str>) -> Self {
+        self.input_null = token;
+        self
+    }
+
+    /// The policy of one column, with the rules matching its name applied
+    pub fn for_column(&self, name: &str) -> NullPolicy {
+        match self.columns.for_column(name) {
+            Some(forced) => self.with_override(&forced),
+            None => self.clone(),
+        }
+    }
+
+    /// Add the override's tokens; without `inherit_nulls` only those are NULL
+    pub fn with_override(&self, forced: &ColumnOverride) -> NullPolicy {
+        let mut policy = self.clone();
+        if forced.inherit_nulls == Some(false) {
+            policy.tokens.clear();
+            policy.default_nulls = false;
+            if policy.blank_values == BlankPolicy::Null {
+                policy.blank_values = BlankPolicy::Empty;
+            }
+        }
+        policy.tokens.extend(forced.null_values.iter().cloned());
+        policy
+    }
+
+    /// The value as it is analyzed, usually trimmed, or `None` if it is NULL
+    pub fn read<'a>(&self, value: &'a str) -> Option<&'a str> {
+        if self.input_null == Some(value) {
+            return None;
+        }
+        let value = self.unescape(value);
+        let blank = !value.is_empty() && value.trim().is_empty();
+        let trimmed = match self.blank_values {
+            BlankPolicy::Value if blank => value,
+            _ => value.trim(),
+        };
+        if self.is_null(trimmed) || (blank && self.blank_values == BlankPolicy::Null) {
+            return None;
+        }
+        Some(trimmed)
+    }
+
+    /// A value that is not NULL as the input format wrote it, see `unescape_input_null`
+    pub fn unescape<'a>(&self, value: &'a str) -> &'a str {
+        match self.input_null {
+            Some(token) => unescape_input_null(token, value),
+            None => value,
+        }
+    }
+
+    fn is_null(&self, value: &str) -> bool {
+        self.tokens.contains(value) || (self.default_nulls && DEFAULT_NULL_VALUES.contains(&value))
+    }
+}
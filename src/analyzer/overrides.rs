@@ -0,0 +1,201 @@
+use crate::types::SqlType;
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Settings forced on the columns matching `column` (an exact name) or
+/// `pattern` (a regex), whatever the data suggests
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnOverride {
+    pub column: Option<String>,
+    pub pattern: Option<String>,
+    #[serde(rename = "type")]
+    pub sql_type: Option<SqlType>,
+    pub not_null: Option<bool>,
+    pub date_format: Option<String>,
+    /// Extra tokens read as NULL in this column
+    #[serde(default)]
+    pub null_values: Vec<String>,
+    /// Whether the global null tokens also apply; when false only
+    /// `null_values` are NULL, so an empty string can be a value
+    pub inherit_nulls: Option<bool>,
+    /// Column name to use in the DDL
+    pub rename: Option<String>,
+}
+
+impl ColumnOverride {
+    /// Apply the fields `other` sets on top of these
+    fn merge(&mut self, other: &ColumnOverride) {
+        if other.sql_type.is_some() {
+            self.sql_type = other.sql_type.clone();
+        }
+        if other.not_null.is_some() {
+            self.not_null = other.not_null;
+        }
+        if other.date_format.is_some() {
+            self.date_format = other.date_format.clone();
+        }
+        if other.inherit_nulls.is_some() {
+            self.inherit_nulls = other.inherit_nulls;
+        }
+        if other.rename.is_some() {
+            self.rename = other.rename.clone();
+        }
+        self.null_values.extend(other.null_values.iter().cloned());
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OverridesFile {
+    #[serde(default)]
+    overrides: Vec<ColumnOverride>,
+}
+
+/// The rules of an `--overrides` file, in file order
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    rules: Vec<(Option<Regex>, ColumnOverride)>,
+}
+
+impl Overrides {
+    /// Load a JSON file, or a TOML file when the extension is `.toml`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read overrides file {:?}", path))?;
+
+        let is_toml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        let file: OverridesFile = if is_toml {
+            toml::from_str(&content).map_err(anyhow::Error::from)
+        } else {
+            serde_json::from_str(&content).map_err(anyhow::Error::from)
+        }
+        .with_context(|| format!("Invalid overrides file {:?}", path))?;
+        Overrides::new(file.overrides)
+    }
+
+    pub fn new(overrides: Vec<ColumnOverride>) -> Result<Self> {
+        let rules = overrides
+            .into_iter()
+            .map(|rule| {
+                let regex = match (&rule.column, &rule.pattern) {
+                    (Some(_), None) => None,
+                    (None, Some(pattern)) => Some(
+                        Regex::new(pattern)
+                            .with_context(|| format!("Invalid override pattern '{}'", pattern))?,
+                    ),
+                    _ => bail!("Each override needs exactly one of 'column' or 'pattern'"),
+                };
+                Ok((regex, rule))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Overrides { rules })
+    }
+
+    /// Add `--fnull-column` tokens as rules after those of the file
+    pub fn with_null_values(mut self, tokens: &[(String, String)]) -> Self {
+        for (column, value) in tokens {
+            let rule = ColumnOverride {
+                column: Some(column.clone()),
+                null_values: vec![value.clone()],
+                ..ColumnOverride::default()
+            };
+            self.rules.push((None, rule));
+        }
+        self
+    }
+
+    /// Every rule matching the column merged in file order, so later rules win
+    pub fn for_column(&self, name: &str) -> Option<ColumnOverride> {
+        let mut matched: Option<ColumnOverride> = None;
+        for (regex, rule) in &self.rules {
+            let matches = match regex {
+                Some(regex) => regex.is_match(name),
+                None => rule.column.as_deref() == Some(name),
+            };
+            if matches {
+                matched
+                    .get_or_insert_with(ColumnOverride::default)
+                    .merge(rule);
+            }
+        }
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::Builder;
+
+    #[test]
+    fn test_json_and_toml_overrides() {
+        let json = r#"{"overrides": [
+            {"column": "status_code", "type": "VARCHAR(10)"},
+            {"pattern": "^is_", "type": "SMALLINT", "not_null": true},
+            {"column": "is_active", "rename": "active", "null_values": ["-"], "inherit_nulls": false}
+        ]}"#;
+        let toml = r#"
+# Business rules for the orders feed
+[[overrides]]
+column = "status_code"
+type = "VARCHAR(10)"
+
+[[overrides]]
+pattern = '^is_'     # flags
+type = "SMALLINT"
+not_null = true
+
+[[overrides]]
+column = "is_active"
+rename = "active"
+null_values = [
+    "-",
+]
+inherit_nulls = false
+"#;
+
+        for (suffix, content) in [(".json", json), (".toml", toml)] {
+            let mut file = Builder::new().suffix(suffix).tempfile().unwrap();
+            file.write_all(content.as_bytes()).unwrap();
+            let overrides = Overrides::from_file(file.path()).unwrap();
+
+            let status = overrides.for_column("status_code").unwrap();
+            assert_eq!(status.sql_type, Some(SqlType::Varchar(Some(10))));
+
+            let flag = overrides.for_column("is_active").unwrap();
+            assert_eq!(flag.sql_type, Some(SqlType::SmallInt));
+            assert_eq!(flag.not_null, Some(true));
+            assert_eq!(flag.rename.as_deref(), Some("active"));
+            assert_eq!(flag.null_values, vec!["-"]);
+            assert_eq!(flag.inherit_nulls, Some(false));
+
+            assert!(overrides.for_column("amount").is_none());
+
+            let tokens = [("is_active".to_string(), "?".to_string())];
+            let flag = overrides.with_null_values(&tokens).for_column("is_active");
+            assert_eq!(flag.unwrap().null_values, vec!["-", "?"]);
+        }
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        let both = ColumnOverride {
+            column: Some("a".to_string()),
+            pattern: Some("^a".to_string()),
+            ..ColumnOverride::default()
+        };
+        assert!(Overrides::new(vec![both]).is_err());
+        assert!(Overrides::new(vec![ColumnOverride::default()]).is_err());
+
+        assert!(toml::from_str::<OverridesFile>("[columns]\nx = 1").is_err());
+        assert!(toml::from_str::<OverridesFile>("column = \"a\"").is_err());
+    }
+}
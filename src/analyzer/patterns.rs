@@ -1,5 +1,7 @@
+use crate::analyzer::type_set::{TypeSet, TypeSize};
+use crate::cli::NumericIdPolicy;
 use crate::types::SqlType;
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
 use regex::Regex;
 use std::sync::OnceLock;
 
@@ -9,6 +11,7 @@ pub struct TypePatterns {
     date: Regex,
     time: Regex,
     datetime: Regex,
+    fractional_seconds: Regex,
 }
 
 static PATTERNS: OnceLock<TypePatterns> = OnceLock::new();
@@ -25,8 +28,10 @@ impl TypePatterns {
             integer: Regex::new(r"^[+-]?\d+$").unwrap(),
             double: Regex::new(r"^[+-]?(\d+\.?\d*|\.\d+)([eE][+-]?\d+)?$").unwrap(),
             date: Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap(),
-            time: Regex::new(r"^\d{1,2}:\d{2}:\d{2}$").unwrap(),
-            datetime: Regex::new(r"^\d{4}-\d{2}-\d{2} \d{1,2}:\d{2}:\d{2}$").unwrap(),
+            time: Regex::new(r"^\d{1,2}:\d{2}:\d{2}(\.\d{1,9})?$").unwrap(),
+            datetime: Regex::new(r"^\d{4}-\d{2}-\d{2}[ T]\d{1,2}:\d{2}:\d{2}(\.\d{1,9})?$")
+                .unwrap(),
+            fractional_seconds: Regex::new(r":\d{2}\.(\d+)").unwrap(),
         }
     }
 
@@ -35,6 +40,24 @@ impl TypePatterns {
     }
 }
 
+/// The native cell type of a workbook column, which its text alone would lose
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellHint {
+    /// Some text cell reads like a number or date, such as `00123` stored as text
+    Text,
+    /// Some number cell has a fraction, so the column is floating point
+    Float,
+    /// Every cell is a boolean
+    Boolean,
+    /// Every cell is a date serial without a time of day
+    Date,
+    /// Every cell is a date serial, some with a time of day
+    DateTime,
+}
+
+/// The hint of each column by position, `None` where its cells say nothing
+pub type CellHints = Vec<Option<CellHint>>;
+
 #[derive(Debug, Clone)]
 pub struct TypeInferencer {
     date_format: String,
@@ -42,8 +65,23 @@ pub struct TypeInferencer {
     datetime_format: String,
     true_values: Vec<String>,
     false_values: Vec<String>,
+    exact_decimals: bool,
+    max_decimal_digits: usize,
+    numeric_ids: NumericIdPolicy,
+    cell_hint: Option<CellHint>,
 }
 
+/// Common date formats, tried after any custom `--fdate` format. When every value
+/// of a column fits several, the earliest wins, so month-first is assumed over
+/// day-first until some day is above 12.
+pub const DATE_FORMATS: [&str; 10] = [
+    "%Y-%m-%d", "%m/%d/%Y", "%d/%m/%Y", "%Y/%m/%d", "%m-%d-%Y", "%d-%m-%Y", "%d.%m.%Y", "%d-%b-%Y",
+    "%d %b %Y", "%Y%m%d",
+];
+
+/// Widest DECIMAL precision every built-in dialect accepts
+const DEFAULT_MAX_DECIMAL_DIGITS: usize = 38;
+
 impl TypeInferencer {
     pub fn new() -> Self {
         TypeInferencer {
@@ -64,6 +102,10 @@ impl TypeInferencer {
                 "n".to_string(),
                 "0".to_string(),
             ],
+            exact_decimals: false,
+            max_decimal_digits: DEFAULT_MAX_DECIMAL_DIGITS,
+            numeric_ids: NumericIdPolicy::default(),
+            cell_hint: None,
         }
     }
 
@@ -85,59 +127,189 @@ impl TypeInferencer {
         inferencer
     }
 
+    /// Try this date format before the common ones
+    pub fn with_date_format(mut self, format: String) -> Self {
+        self.date_format = format;
+        self
+    }
+
+    /// Also offer DECIMAL for fixed-point values, which is narrower than DOUBLE PRECISION
+    pub fn with_exact_decimals(mut self, exact: bool) -> Self {
+        self.exact_decimals = exact;
+        self
+    }
+
+    /// Largest DECIMAL precision the target dialect accepts; longer numbers are text
+    pub fn with_max_decimal_digits(mut self, digits: usize) -> Self {
+        // SqlType::Decimal keeps its precision in a u16
+        self.max_decimal_digits = digits.min(u16::MAX as usize);
+        self
+    }
+
+    pub fn max_decimal_digits(&self) -> usize {
+        self.max_decimal_digits
+    }
+
+    pub fn exact_decimals(&self) -> bool {
+        self.exact_decimals
+    }
+
+    /// Which number-like identifiers are inferred as text rather than numbers
+    pub fn with_numeric_ids(mut self, policy: NumericIdPolicy) -> Self {
+        self.numeric_ids = policy;
+        self
+    }
+
+    /// Infer values as their workbook cells were typed
+    pub fn with_cell_hint(mut self, hint: Option<CellHint>) -> Self {
+        self.cell_hint = hint;
+        self
+    }
+
+    pub fn numeric_ids(&self) -> NumericIdPolicy {
+        self.numeric_ids
+    }
+
     pub fn with_boolean_values(mut self, true_vals: Vec<String>, false_vals: Vec<String>) -> Self {
         self.true_values = true_vals;
         self.false_values = false_vals;
         self
     }
 
+    /// The narrowest type that accepts the value
     pub fn infer_type(&self, value: &str) -> SqlType {
         let trimmed = value.trim();
+        let (int_digits, frac_digits) = decimal_digits(trimmed).unwrap_or_default();
+        self.candidate_types(trimmed).narrowest_type(TypeSize {
+            max_length: trimmed.len().max(1),
+            int_digits,
+            frac_digits,
+        })
+    }
+
+    /// Every type the value can be loaded as. VARCHAR accepts anything, and a
+    /// date is also accepted as a DATETIME at midnight.
+    pub fn candidate_types(&self, value: &str) -> TypeSet {
+        let trimmed = value.trim();
+        let mut candidates = TypeSet::VARCHAR;
 
         if trimmed.is_empty() {
-            return SqlType::Varchar(Some(1));
+            return candidates;
         }
 
         let patterns = TypePatterns::get();
 
         // Check boolean
         if self.is_boolean_true(trimmed) || self.is_boolean_false(trimmed) {
-            return SqlType::Boolean;
+            candidates |= TypeSet::BOOLEAN;
         }
 
         // Check integer
-        if patterns.integer.is_match(trimmed) {
+        let is_integer = patterns.integer.is_match(trimmed);
+        if is_integer {
             if let Ok(num) = trimmed.parse::<i64>() {
-                return match num {
-                    -32768..=32767 => SqlType::SmallInt,
-                    -2147483648..=2147483647 => SqlType::Integer,
-                    _ => SqlType::BigInt,
+                candidates |= match num {
+                    -128..=127 => {
+                        TypeSet::TINYINT | TypeSet::SMALLINT | TypeSet::INTEGER | TypeSet::BIGINT
+                    }
+                    -32768..=32767 => TypeSet::SMALLINT | TypeSet::INTEGER | TypeSet::BIGINT,
+                    -2147483648..=2147483647 => TypeSet::INTEGER | TypeSet::BIGINT,
+                    _ => TypeSet::BIGINT,
                 };
             }
         }
 
-        // Check double
-        if patterns.double.is_match(trimmed) && trimmed.parse::<f64>().is_ok() {
-            return SqlType::DoublePrecision;
+        // Check double, but not for integers beyond BIGINT, which it would round
+        let beyond_bigint = is_integer && !candidates.contains(TypeSet::BIGINT);
+        if !beyond_bigint
+            && patterns.double.is_match(trimmed)
+            && trimmed.parse::<f64>().is_ok_and(|num| num.is_finite())
+        {
+            candidates |= TypeSet::DOUBLE;
+        }
+
+        // Check fixed-point: any integer, and fractions when exact decimals are on
+        if (is_integer || self.exact_decimals)
+            && decimal_digits(trimmed).is_some()
+            && self.decimal_overflow(trimmed).is_none()
+        {
+            candidates |= TypeSet::DECIMAL;
+        }
+
+        // Identifiers such as zip codes keep their leading zeros only as text
+        if self.numeric_ids != NumericIdPolicy::Never && self.is_identifier_like(trimmed) {
+            candidates = candidates - TypeSet::NUMERIC;
         }
 
         // Check date
         if self.is_date(trimmed) {
-            return SqlType::Date;
+            candidates |= TypeSet::DATE | TypeSet::DATETIME;
         }
 
         // Check time
         if self.is_time(trimmed) {
-            return SqlType::Time;
+            candidates |= TypeSet::TIME;
         }
 
-        // Check datetime
+        // Check datetime; a naive timestamp can also be loaded with an offset
         if self.is_datetime(trimmed) {
-            return SqlType::DateTime;
+            candidates |= TypeSet::DATETIME | TypeSet::DATETIMETZ;
+        }
+
+        // Check timestamps with a time zone offset
+        if self.is_datetime_tz(trimmed) {
+            candidates |= TypeSet::DATETIMETZ;
         }
 
-        // Default to VARCHAR with length
-        SqlType::Varchar(Some(trimmed.len()))
+        // Check JSON arrays and objects
+        if self.is_json(trimmed) {
+            candidates |= TypeSet::JSON;
+        }
+
+        match self.cell_hint {
+            Some(CellHint::Text) => TypeSet::VARCHAR,
+            // Float cells are binary fractions, so their text is no exact decimal
+            Some(CellHint::Float) => candidates - TypeSet::DECIMAL,
+            Some(CellHint::Boolean) => candidates & (TypeSet::BOOLEAN | TypeSet::VARCHAR),
+            Some(CellHint::Date) => candidates & (TypeSet::DATE | TypeSet::VARCHAR),
+            Some(CellHint::DateTime) => candidates & (TypeSet::DATETIME | TypeSet::VARCHAR),
+            None => candidates,
+        }
+    }
+
+    /// True for an integer literal with a `+` sign or a leading zero, which a
+    /// numeric column would not keep
+    pub fn is_identifier_like(&self, value: &str) -> bool {
+        if !TypePatterns::get().integer.is_match(value) {
+            return false;
+        }
+        let digits = value.trim_start_matches('-');
+        value.starts_with('+') || (digits.len() > 1 && digits.starts_with('0'))
+    }
+
+    /// Digit count of an exact numeric value that is too long for a DECIMAL
+    pub fn decimal_overflow(&self, value: &str) -> Option<usize> {
+        let (int_digits, frac_digits) = decimal_digits(value)?;
+        let exact = frac_digits == 0 && !value.contains('.') || self.exact_decimals;
+        let digits = int_digits + frac_digits;
+        (exact && digits > self.max_decimal_digits).then_some(digits)
+    }
+
+    /// True for a JSON array or object; bare JSON scalars are typed as their own values
+    pub fn is_json(&self, value: &str) -> bool {
+        (value.starts_with('[') || value.starts_with('{'))
+            && serde_json::from_str::<serde_json::Value>(value).is_ok()
+    }
+
+    /// Interpret a value using the configured TRUE/FALSE tokens
+    pub fn parse_boolean(&self, value: &str) -> Option<bool> {
+        if self.is_boolean_true(value) {
+            Some(true)
+        } else if self.is_boolean_false(value) {
+            Some(false)
+        } else {
+            None
+        }
     }
 
     fn is_boolean_true(&self, value: &str) -> bool {
@@ -153,56 +325,119 @@ impl TypeInferencer {
     }
 
     fn is_date(&self, value: &str) -> bool {
+        self.parse_date(value).is_some()
+    }
+
+    fn is_time(&self, value: &str) -> bool {
+        self.parse_time(value).is_some()
+    }
+
+    fn is_datetime(&self, value: &str) -> bool {
+        self.parse_datetime(value).is_some()
+    }
+
+    fn is_datetime_tz(&self, value: &str) -> bool {
+        self.parse_datetime_tz(value).is_some()
+    }
+
+    pub fn parse_date(&self, value: &str) -> Option<NaiveDate> {
         // First try the default pattern
         let patterns = TypePatterns::get();
-        if patterns.date.is_match(value) && NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
-            return true;
+        if patterns.date.is_match(value) {
+            if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                return Some(date);
+            }
         }
 
-        // Try custom format if different
-        if self.date_format != "%Y-%m-%d"
-            && NaiveDate::parse_from_str(value, &self.date_format).is_ok()
-        {
-            return true;
+        // Then the custom format and the common ones, first match wins
+        match self.matching_date_formats(value) {
+            0 => None,
+            mask => {
+                let format = self.date_format_at(mask.trailing_zeros() as usize);
+                NaiveDate::parse_from_str(value, format).ok()
+            }
+        }
+    }
+
+    /// Bit mask of the date formats that read the value, indexed as in `date_format_at`
+    pub fn matching_date_formats(&self, value: &str) -> u32 {
+        let custom = self.date_format != DATE_FORMATS[0];
+        let mut mask = 0;
+        if custom && NaiveDate::parse_from_str(value, &self.date_format).is_ok() {
+            mask |= 1;
         }
 
-        false
+        // Every common format starts with a digit and is 8 to 11 characters long
+        if (8..=11).contains(&value.len()) && value.starts_with(|c: char| c.is_ascii_digit()) {
+            for (i, format) in DATE_FORMATS.iter().enumerate() {
+                if NaiveDate::parse_from_str(value, format).is_ok() {
+                    mask |= 1 << (i + custom as usize);
+                }
+            }
+        }
+        mask
     }
 
-    fn is_time(&self, value: &str) -> bool {
+    /// The custom date format, if any, followed by `DATE_FORMATS`
+    pub fn date_format_at(&self, index: usize) -> &str {
+        match (self.date_format != DATE_FORMATS[0], index) {
+            (true, 0) => &self.date_format,
+            (true, i) => DATE_FORMATS[i - 1],
+            (false, i) => DATE_FORMATS[i],
+        }
+    }
+
+    pub fn parse_time(&self, value: &str) -> Option<NaiveTime> {
         // First try the default pattern
         let patterns = TypePatterns::get();
-        if patterns.time.is_match(value) && NaiveTime::parse_from_str(value, "%H:%M:%S").is_ok() {
-            return true;
+        if patterns.time.is_match(value) {
+            if let Ok(time) = NaiveTime::parse_from_str(value, "%H:%M:%S%.f") {
+                return Some(time);
+            }
         }
 
         // Try custom format if different
-        if self.time_format != "%H:%M:%S"
-            && NaiveTime::parse_from_str(value, &self.time_format).is_ok()
-        {
-            return true;
+        if self.time_format != "%H:%M:%S" {
+            return NaiveTime::parse_from_str(value, &self.time_format).ok();
         }
 
-        false
+        None
     }
 
-    fn is_datetime(&self, value: &str) -> bool {
+    pub fn parse_datetime(&self, value: &str) -> Option<NaiveDateTime> {
         // First try the default pattern
         let patterns = TypePatterns::get();
-        if patterns.datetime.is_match(value)
-            && NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").is_ok()
-        {
-            return true;
+        if patterns.datetime.is_match(value) {
+            let format = if value.as_bytes()[10] == b'T' {
+                "%Y-%m-%dT%H:%M:%S%.f"
+            } else {
+                "%Y-%m-%d %H:%M:%S%.f"
+            };
+            if let Ok(datetime) = NaiveDateTime::parse_from_str(value, format) {
+                return Some(datetime);
+            }
         }
 
         // Try custom format if different
-        if self.datetime_format != "%Y-%m-%d %H:%M:%S"
-            && NaiveDateTime::parse_from_str(value, &self.datetime_format).is_ok()
-        {
-            return true;
+        if self.datetime_format != "%Y-%m-%d %H:%M:%S" {
+            return NaiveDateTime::parse_from_str(value, &self.datetime_format).ok();
+        }
+
+        None
+    }
+
+    /// A timestamp with a `Z` or numeric offset: ISO 8601 with a `T` or space
+    /// separator (which covers RFC 3339), or RFC 2822
+    pub fn parse_datetime_tz(&self, value: &str) -> Option<DateTime<FixedOffset>> {
+        // Every accepted form has a time of day
+        if !value.contains(':') {
+            return None;
         }
 
-        false
+        DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%#z")
+            .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z"))
+            .or_else(|_| DateTime::parse_from_rfc2822(value))
+            .ok()
     }
 }
 
@@ -212,6 +447,28 @@ impl Default for TypeInferencer {
     }
 }
 
+/// Number of fractional-second digits in a time or timestamp, e.g. 3 for `12:00:00.250`
+pub fn fractional_second_digits(value: &str) -> usize {
+    TypePatterns::get()
+        .fractional_seconds
+        .captures(value)
+        .map_or(0, |c| c[1].len())
+}
+
+/// Integer and fractional digit counts of a plain decimal literal such as `-012.50`.
+/// Leading zeros of the integer part are not counted; exponents are not accepted.
+pub fn decimal_digits(value: &str) -> Option<(usize, usize)> {
+    let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+
+    let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if int_part.len() + frac_part.len() == 0 || !all_digits(int_part) || !all_digits(frac_part) {
+        return None;
+    }
+
+    Some((int_part.trim_start_matches('0').len(), frac_part.len()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -259,6 +516,36 @@ mod tests {
 
         assert_eq!(inferencer.infer_type("2023-12-25"), SqlType::Date);
         assert_eq!(inferencer.infer_type("2023-01-01"), SqlType::Date);
+        assert_eq!(inferencer.infer_type("25/12/2023"), SqlType::Date);
+        assert_eq!(inferencer.infer_type("25-Dec-2023"), SqlType::Date);
+        assert_eq!(inferencer.infer_type("25.12.2023"), SqlType::Date);
+    }
+
+    #[test]
+    fn test_date_format_library() {
+        let inferencer = TypeInferencer::new();
+        let formats = |value: &str| {
+            let mask = inferencer.matching_date_formats(value);
+            (0..32)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| inferencer.date_format_at(i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(formats("2024-03-15"), vec!["%Y-%m-%d"]);
+        assert_eq!(formats("03/04/2024"), vec!["%m/%d/%Y", "%d/%m/%Y"]);
+        assert_eq!(formats("13/04/2024"), vec!["%d/%m/%Y"]);
+        assert_eq!(formats("20240315"), vec!["%Y%m%d"]);
+        assert!(formats("hello").is_empty());
+
+        // A custom format comes first
+        let custom = TypeInferencer::with_formats(Some("%d/%m/%Y".to_string()), None, None);
+        assert_eq!(custom.date_format_at(0), "%d/%m/%Y");
+        assert_eq!(custom.matching_date_formats("03/04/2024"), 0b1101);
+        assert_eq!(
+            custom.parse_date("03/04/2024"),
+            NaiveDate::from_ymd_opt(2024, 4, 3)
+        );
     }
 
     #[test]
@@ -267,6 +554,10 @@ mod tests {
 
         assert_eq!(inferencer.infer_type("14:30:00"), SqlType::Time);
         assert_eq!(inferencer.infer_type("9:15:30"), SqlType::Time);
+        assert_eq!(inferencer.infer_type("12:00:00.123456"), SqlType::Time);
+        assert_eq!(fractional_second_digits("12:00:00.123456"), 6);
+        assert_eq!(fractional_second_digits("2024-03-01T10:00:00.25+02:00"), 2);
+        assert_eq!(fractional_second_digits("12:00:00"), 0);
     }
 
     #[test]
@@ -281,6 +572,41 @@ mod tests {
             inferencer.infer_type("2023-01-01 9:15:30"),
             SqlType::DateTime
         );
+        assert_eq!(
+            inferencer.infer_type("2023-12-25T14:30:00"),
+            SqlType::DateTime
+        );
+        assert_eq!(
+            inferencer.infer_type("2023-12-25 14:30:00.5"),
+            SqlType::DateTime
+        );
+    }
+
+    #[test]
+    fn test_zoned_datetime_inference() {
+        let inferencer = TypeInferencer::new();
+
+        for value in [
+            "2024-03-01T10:00:00Z",
+            "2024-03-01T10:00:00.250+02:00",
+            "2024-03-01 10:00:00-0500",
+            "Fri, 01 Mar 2024 10:00:00 +0000",
+        ] {
+            assert_eq!(
+                inferencer.infer_type(value),
+                SqlType::DateTimeTz,
+                "{}",
+                value
+            );
+        }
+
+        let parsed = inferencer
+            .parse_datetime_tz("2024-03-01T10:00:00+02:00")
+            .unwrap();
+        assert_eq!(parsed.offset().local_minus_utc(), 7200);
+        assert!(inferencer
+            .parse_datetime_tz("2024-03-01 10:00:00")
+            .is_none());
     }
 
     #[test]
@@ -295,6 +621,86 @@ mod tests {
         assert_eq!(inferencer.infer_type(""), SqlType::Varchar(Some(1)));
     }
 
+    #[test]
+    fn test_decimal_digits() {
+        assert_eq!(decimal_digits("-012.50"), Some((2, 2)));
+        assert_eq!(decimal_digits("0.001"), Some((0, 3)));
+        assert_eq!(decimal_digits("42"), Some((2, 0)));
+        assert_eq!(decimal_digits(".5"), Some((0, 1)));
+        assert_eq!(decimal_digits("1e5"), None);
+        assert_eq!(decimal_digits("-"), None);
+        assert_eq!(decimal_digits("1.2.3"), None);
+    }
+
+    #[test]
+    fn test_exact_decimal_inference() {
+        let inferencer = TypeInferencer::new().with_exact_decimals(true);
+
+        assert_eq!(
+            inferencer.infer_type("-012.50"),
+            SqlType::Decimal {
+                precision: 4,
+                scale: 2
+            }
+        );
+        assert_eq!(inferencer.infer_type("42"), SqlType::SmallInt);
+        assert_eq!(inferencer.infer_type("1.5e3"), SqlType::DoublePrecision);
+        assert_eq!(
+            TypeInferencer::new().infer_type("12.50"),
+            SqlType::DoublePrecision
+        );
+    }
+
+    #[test]
+    fn test_identifier_like_values() {
+        let inferencer = TypeInferencer::new();
+
+        assert!(inferencer.is_identifier_like("00123"));
+        assert!(inferencer.is_identifier_like("+44"));
+        assert!(!inferencer.is_identifier_like("0"));
+        assert!(!inferencer.is_identifier_like("0.5"));
+        assert!(!inferencer.is_identifier_like("-12"));
+        assert_eq!(inferencer.infer_type("02134"), SqlType::Varchar(Some(5)));
+
+        let numbers = TypeInferencer::new().with_numeric_ids(NumericIdPolicy::Never);
+        assert_eq!(numbers.infer_type("02134"), SqlType::SmallInt);
+    }
+
+    #[test]
+    fn test_integers_beyond_bigint() {
+        let inferencer = TypeInferencer::new();
+
+        assert_eq!(
+            inferencer.infer_type("12345678901234567890"),
+            SqlType::Decimal {
+                precision: 20,
+                scale: 0
+            }
+        );
+        assert_eq!(
+            inferencer.infer_type("9223372036854775807"),
+            SqlType::BigInt
+        );
+
+        let narrow = TypeInferencer::new().with_max_decimal_digits(19);
+        assert_eq!(
+            narrow.infer_type("12345678901234567890"),
+            SqlType::Varchar(Some(20))
+        );
+        assert_eq!(narrow.decimal_overflow("12345678901234567890"), Some(20));
+        assert_eq!(narrow.decimal_overflow("1234567890123456789.0"), None);
+    }
+
+    #[test]
+    fn test_json_inference() {
+        let inferencer = TypeInferencer::new();
+
+        assert_eq!(inferencer.infer_type("[1, 2]"), SqlType::Json);
+        assert_eq!(inferencer.infer_type(r#"{"a": null}"#), SqlType::Json);
+        assert_eq!(inferencer.infer_type("[1, 2"), SqlType::Varchar(Some(5)));
+        assert_eq!(inferencer.infer_type("\"text\""), SqlType::Varchar(Some(6)));
+    }
+
     #[test]
     fn test_custom_boolean_values() {
         let inferencer = TypeInferencer::new().with_boolean_values(
@@ -0,0 +1,346 @@
+use crate::analyzer::histogram::Histogram;
+use crate::analyzer::patterns::TypeInferencer;
+use crate::analyzer::type_set::TypeSet;
+use crate::types::HistogramBucket;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use std::cmp::Ordering;
+
+/// A number compared exactly when both sides are integers
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Int(i128),
+    Float(f64),
+}
+
+impl Number {
+    fn parse(value: &str) -> Option<Number> {
+        match value.parse::<i128>() {
+            Ok(n) => Some(Number::Int(n)),
+            Err(_) => value
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(Number::Float),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(n) => n as f64,
+            Number::Float(n) => n,
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a.partial_cmp(b),
+            _ => self.as_f64().partial_cmp(&other.as_f64()),
+        }
+    }
+}
+
+/// The lowest and highest value seen by some key, as written in the input
+#[derive(Debug, Clone)]
+struct Extremes<K> {
+    low: (K, String),
+    high: (K, String),
+}
+
+impl<K: PartialOrd + Clone> Extremes<K> {
+    fn widen(slot: &mut Option<Extremes<K>>, key: K, value: &str) {
+        match slot {
+            None => {
+                *slot = Some(Extremes {
+                    low: (key.clone(), value.to_string()),
+                    high: (key, value.to_string()),
+                })
+            }
+            Some(extremes) => {
+                if key < extremes.low.0 {
+                    extremes.low = (key, value.to_string());
+                } else if key > extremes.high.0 {
+                    extremes.high = (key, value.to_string());
+                }
+            }
+        }
+    }
+
+    fn bounds(&self) -> (&str, &str) {
+        (&self.low.1, &self.high.1)
+    }
+}
+
+/// Lowest and highest values of a column in every domain its values could
+/// be read in, so the bounds follow the type even after it is promoted
+#[derive(Debug, Default)]
+pub struct ValueRange {
+    /// Text by byte length, then bytewise
+    text: Option<Extremes<(usize, String)>>,
+    booleans: Option<Extremes<bool>>,
+    numbers: Option<Extremes<Number>>,
+    /// One per date format, indexed as in `TypeInferencer::date_format_at`
+    dates: Vec<Option<Extremes<NaiveDate>>>,
+    times: Option<Extremes<NaiveTime>>,
+    /// Timestamps in UTC, naive ones taken as UTC
+    timestamps: Option<Extremes<NaiveDateTime>>,
+    histograms: Option<Histograms>,
+}
+
+/// Distribution of the numbers, dates, times and timestamps; dates as days
+/// from the common era, times and timestamps in seconds
+#[derive(Debug, Default)]
+struct Histograms {
+    numbers: Histogram,
+    dates: Vec<Histogram>,
+    times: Histogram,
+    timestamps: Histogram,
+}
+
+impl ValueRange {
+    /// Also keep a histogram of each typed domain
+    pub fn with_histograms(mut self) -> Self {
+        self.histograms = Some(Histograms::default());
+        self
+    }
+
+    pub fn record(&mut self, value: &str, types: TypeSet, inferencer: &TypeInferencer) {
+        Extremes::widen(&mut self.text, (value.len(), value.to_string()), value);
+
+        if types.contains(TypeSet::BOOLEAN) {
+            if let Some(flag) = inferencer.parse_boolean(value) {
+                Extremes::widen(&mut self.booleans, flag, value);
+            }
+        }
+        if !(types & TypeSet::NUMERIC).is_empty() {
+            if let Some(number) = Number::parse(value) {
+                Extremes::widen(&mut self.numbers, number, value);
+                if let Some(histograms) = &mut self.histograms {
+                    histograms.numbers.record(number.as_f64());
+                }
+            }
+        }
+        if types.contains(TypeSet::TIME) {
+            if let Some(time) = inferencer.parse_time(value) {
+                Extremes::widen(&mut self.times, time, value);
+                if let Some(histograms) = &mut self.histograms {
+                    histograms.times.record(seconds_from_midnight(time));
+                }
+            }
+        }
+        if (types & TypeSet::TIMESTAMPS).is_empty() {
+            return;
+        }
+        // A bare date in a DATETIME column is midnight, as parse writes it
+        let instant = inferencer
+            .parse_datetime_tz(value)
+            .map(|t| t.naive_utc())
+            .or_else(|| inferencer.parse_datetime(value))
+            .or_else(|| {
+                types
+                    .contains(TypeSet::DATETIME)
+                    .then(|| inferencer.parse_date(value))
+                    .flatten()
+                    .map(|d| d.and_time(NaiveTime::MIN))
+            });
+        if let Some(instant) = instant {
+            Extremes::widen(&mut self.timestamps, instant, value);
+            if let Some(histograms) = &mut self.histograms {
+                histograms.timestamps.record(epoch_seconds(instant));
+            }
+        }
+    }
+
+    /// Record a date under each format in `formats`, a
+    /// `TypeInferencer::matching_date_formats` mask
+    pub fn record_date(&mut self, value: &str, formats: u32, inferencer: &TypeInferencer) {
+        for index in 0..u32::BITS as usize {
+            if formats & (1 << index) == 0 {
+                continue;
+            }
+            let format = inferencer.date_format_at(index);
+            if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+                if self.dates.len() <= index {
+                    self.dates.resize(index + 1, None);
+                }
+                Extremes::widen(&mut self.dates[index], date, value);
+                if let Some(histograms) = &mut self.histograms {
+                    if histograms.dates.len() <= index {
+                        histograms.dates.resize(index + 1, Histogram::default());
+                    }
+                    histograms.dates[index].record(date.num_days_from_ce() as f64);
+                }
+            }
+        }
+    }
+
+    /// The lowest and highest value in the domain of `kind`, dates read with
+    /// the format at `date_format`; text, by byte length, for other kinds or
+    /// when no value was read in that domain
+    pub fn bounds(&self, kind: TypeSet, date_format: Option<usize>) -> Option<(&str, &str)> {
+        let typed = if kind == TypeSet::BOOLEAN {
+            self.booleans.as_ref().map(Extremes::bounds)
+        } else if TypeSet::NUMERIC.contains(kind) {
+            self.numbers.as_ref().map(Extremes::bounds)
+        } else if kind == TypeSet::DATE {
+            date_format
+                .and_then(|index| self.dates.get(index)?.as_ref())
+                .map(Extremes::bounds)
+        } else if kind == TypeSet::TIME {
+            self.times.as_ref().map(Extremes::bounds)
+        } else if TypeSet::TIMESTAMPS.contains(kind) {
+            self.timestamps.as_ref().map(Extremes::bounds)
+        } else {
+            None
+        };
+        typed.or_else(|| self.text.as_ref().map(Extremes::bounds))
+    }
+
+    /// Up to `buckets` equi-width buckets of the values in the domain of
+    /// `kind`, with bounds written the way the type reads them
+    pub fn histogram(
+        &self,
+        kind: TypeSet,
+        date_format: Option<usize>,
+        inferencer: &TypeInferencer,
+        buckets: usize,
+    ) -> Vec<HistogramBucket> {
+        let Some(histograms) = &self.histograms else {
+            return Vec::new();
+        };
+        let (histogram, label): (Option<&Histogram>, Box<dyn Fn(f64) -> String>) =
+            if TypeSet::NUMERIC.contains(kind) {
+                (Some(&histograms.numbers), Box::new(number_label))
+            } else if kind == TypeSet::DATE {
+                let index = date_format.unwrap_or_default();
+                let format = inferencer.date_format_at(index).to_string();
+                (
+                    histograms.dates.get(index),
+                    Box::new(move |days| {
+                        NaiveDate::from_num_days_from_ce_opt(days.floor() as i32)
+                            .map_or_else(|| number_label(days), |d| d.format(&format).to_string())
+                    }),
+                )
+            } else if kind == TypeSet::TIME {
+                (Some(&histograms.times), Box::new(time_label))
+            } else if TypeSet::TIMESTAMPS.contains(kind) {
+                (Some(&histograms.timestamps), Box::new(timestamp_label))
+            } else {
+                (None, Box::new(number_label))
+            };
+
+        histogram
+            .map(|h| h.buckets(buckets))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(low, high, count)| HistogramBucket {
+                low: label(low),
+                high: label(high),
+                count,
+            })
+            .collect()
+    }
+
+    /// The narrowest integer kind holding every number, if all were integers
+    pub fn integer_kind(&self) -> Option<TypeSet> {
+        let extremes = self.numbers.as_ref()?;
+        let (Number::Int(low), Number::Int(high)) = (extremes.low.0, extremes.high.0) else {
+            return None;
+        };
+        [
+            (TypeSet::SMALLINT, i16::MIN as i128, i16::MAX as i128),
+            (TypeSet::INTEGER, i32::MIN as i128, i32::MAX as i128),
+            (TypeSet::BIGINT, i64::MIN as i128, i64::MAX as i128),
+        ]
+        .into_iter()
+        .find(|(_, min, max)| *min <= low && high <= *max)
+        .map(|(kind, _, _)| kind)
+    }
+}
+
+fn seconds_from_midnight(time: NaiveTime) -> f64 {
+    time.num_seconds_from_midnight() as f64 + time.nanosecond() as f64 / 1e9
+}
+
+fn epoch_seconds(instant: NaiveDateTime) -> f64 {
+    let utc = instant.and_utc();
+    utc.timestamp() as f64 + utc.timestamp_subsec_nanos() as f64 / 1e9
+}
+
+/// A bucket bound rounded to six decimals, without trailing zeros
+pub fn number_label(at: f64) -> String {
+    let rounded = (at * 1e6).round() / 1e6;
+    // Avoid printing -0
+    format!("{}", rounded + 0.0)
+}
+
+fn time_label(seconds: f64) -> String {
+    NaiveTime::from_num_seconds_from_midnight_opt(seconds.floor() as u32, 0).map_or_else(
+        || "24:00:00".to_string(),
+        |t| t.format("%H:%M:%S").to_string(),
+    )
+}
+
+fn timestamp_label(seconds: f64) -> String {
+    DateTime::from_timestamp(seconds.floor() as i64, 0).map_or_else(
+        || number_label(seconds),
+        |t| t.format("%Y-%m-%d %H:%M:%S").to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(values: &[&str], inferencer: &TypeInferencer) -> ValueRange {
+        let mut range = ValueRange::default();
+        for value in values {
+            let types = inferencer.candidate_types(value);
+            range.record(value, types, inferencer);
+            if types.contains(TypeSet::DATE) {
+                let formats = inferencer.matching_date_formats(value);
+                range.record_date(value, formats, inferencer);
+            }
+        }
+        range
+    }
+
+    #[test]
+    fn test_bounds_by_domain() {
+        let inferencer = TypeInferencer::new();
+
+        let numbers = range(&["9", "10", "-2.5", "100"], &inferencer);
+        assert_eq!(numbers.bounds(TypeSet::DOUBLE, None), Some(("-2.5", "100")));
+        assert_eq!(numbers.bounds(TypeSet::VARCHAR, None), Some(("9", "-2.5")));
+
+        let integers = range(&["9", "40000"], &inferencer);
+        assert_eq!(integers.integer_kind(), Some(TypeSet::INTEGER));
+        assert_eq!(numbers.integer_kind(), None);
+
+        // Day-first dates order by date, not by text
+        let dates = range(&["31/01/2024", "01/12/2023", "15/06/2024"], &inferencer);
+        let index = inferencer
+            .matching_date_formats("31/01/2024")
+            .trailing_zeros() as usize;
+        assert_eq!(
+            dates.bounds(TypeSet::DATE, Some(index)),
+            Some(("01/12/2023", "15/06/2024"))
+        );
+
+        let times = range(
+            &["2024-01-05 10:00:00+02:00", "2024-01-05 09:00:00"],
+            &inferencer,
+        );
+        assert_eq!(
+            times.bounds(TypeSet::DATETIMETZ, None),
+            Some(("2024-01-05 10:00:00+02:00", "2024-01-05 09:00:00"))
+        );
+    }
+}
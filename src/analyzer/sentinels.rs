@@ -0,0 +1,280 @@
+use crate::analyzer::lattice::PromotionLattice;
+use crate::analyzer::patterns::TypeInferencer;
+use crate::analyzer::type_set::{TypeSet, TypeSize};
+use crate::types::{Sentinel, SqlType};
+use chrono::Datelike;
+use std::collections::HashMap;
+
+/// Distinct values kept per kind; later ones only widen the kind's totals
+const MAX_VALUES_PER_KIND: usize = 1000;
+/// Most distinct values a kind may have for them all to be taken as sentinels
+const MAX_SENTINEL_TOKENS: usize = 3;
+/// Times a far-out value must repeat before it looks like a sentinel
+const MIN_OUTLIER_COUNT: usize = 2;
+
+/// Candidate types and sizes of the values past `MAX_VALUES_PER_KIND`
+#[derive(Debug, Clone, Copy)]
+struct Untracked {
+    candidates: TypeSet,
+    size: TypeSize,
+    /// Lowest and highest numeric or date positions, see `position`
+    low: f64,
+    high: f64,
+}
+
+/// The values whose narrowest type is one kind
+#[derive(Debug, Default)]
+struct KindValues {
+    count: usize,
+    /// Count and candidate types of each distinct value
+    values: HashMap<String, (usize, TypeSet)>,
+    untracked: Option<Untracked>,
+}
+
+/// Distinct values of a column grouped by kind, to ask which type the column
+/// would get if some of them were NULL
+#[derive(Debug, Default)]
+pub struct ValueProfile {
+    kinds: HashMap<TypeSet, KindValues>,
+}
+
+impl ValueProfile {
+    pub fn record(&mut self, value: &str, types: TypeSet, inferencer: &TypeInferencer) {
+        let kind = types.narrowest().unwrap_or(TypeSet::VARCHAR);
+        let group = self.kinds.entry(kind).or_default();
+        group.count += 1;
+
+        if let Some((count, _)) = group.values.get_mut(value) {
+            *count += 1;
+        } else if group.values.len() < MAX_VALUES_PER_KIND {
+            group.values.insert(value.to_string(), (1, types));
+        } else {
+            let untracked = group.untracked.get_or_insert(Untracked {
+                candidates: TypeSet::ALL,
+                size: TypeSize::default(),
+                low: f64::INFINITY,
+                high: f64::NEG_INFINITY,
+            });
+            untracked.candidates = untracked.candidates & types;
+            untracked.size.widen(value);
+            if let Some(at) = position(value, kind, inferencer) {
+                untracked.low = untracked.low.min(at);
+                untracked.high = untracked.high.max(at);
+            }
+        }
+    }
+
+    /// Likely sentinels, and the type the column would get with them read as NULL.
+    ///
+    /// A sentinel is either one of a few tokens that alone turn a typed column
+    /// into VARCHAR, or a repeated number or date far outside the other values.
+    pub fn sentinels(
+        &self,
+        lattice: &PromotionLattice,
+        inferencer: &TypeInferencer,
+    ) -> (Vec<Sentinel>, Option<SqlType>) {
+        let Some(inferred) = self.type_without(&[], lattice) else {
+            return (Vec::new(), None);
+        };
+        let mut sentinels = Vec::new();
+
+        if matches!(inferred, SqlType::Varchar(_)) {
+            let largest = self.kinds.values().map(|g| g.count).max().unwrap_or(0);
+            for group in self.kinds.values() {
+                if group.count == largest
+                    || group.untracked.is_some()
+                    || group.values.len() > MAX_SENTINEL_TOKENS
+                {
+                    continue;
+                }
+                let tokens: Vec<&str> = group.values.keys().map(String::as_str).collect();
+                match self.type_without(&tokens, lattice) {
+                    Some(narrower) if !matches!(narrower, SqlType::Varchar(_)) => {
+                        for (value, (count, _)) in &group.values {
+                            sentinels.push(Sentinel {
+                                value: value.clone(),
+                                count: *count,
+                                reason: format!("turns {} into {}", narrower, inferred),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let excluded: Vec<&str> = sentinels.iter().map(|s| s.value.as_str()).collect();
+        if let Some(base) = self.type_without(&excluded, lattice) {
+            let outliers = self.outliers(&base, &excluded, inferencer);
+            sentinels.extend(outliers);
+        }
+
+        if sentinels.is_empty() {
+            return (sentinels, None);
+        }
+        sentinels.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        let excluded: Vec<&str> = sentinels.iter().map(|s| s.value.as_str()).collect();
+        let without = self.type_without(&excluded, lattice);
+        (sentinels, without)
+    }
+
+    /// The type inferred from every value except `excluded`, or `None` if no
+    /// value is left. Chosen the way `ColumnAnalyzer` chooses it while streaming.
+    fn type_without(&self, excluded: &[&str], lattice: &PromotionLattice) -> Option<SqlType> {
+        let mut candidates = TypeSet::ALL;
+        let mut observed = TypeSet::EMPTY;
+        let mut size = TypeSize::default();
+
+        for (kind, group) in &self.kinds {
+            for (value, (_, types)) in &group.values {
+                if !excluded.contains(&value.as_str()) {
+                    candidates = candidates & *types;
+                    observed |= *kind;
+                    size.widen(value);
+                }
+            }
+            if let Some(untracked) = &group.untracked {
+                candidates = candidates & untracked.candidates;
+                observed |= *kind;
+                size.merge(untracked.size);
+            }
+        }
+        if observed.is_empty() {
+            return None;
+        }
+
+        let (joined, _) = lattice.join_all(observed);
+        let kind = if candidates.contains(joined) {
+            joined
+        } else {
+            candidates.narrowest().unwrap_or(TypeSet::VARCHAR)
+        };
+        Some(kind.sql_type(size))
+    }
+
+    /// The lowest or highest value when it repeats and lies further from the
+    /// next value than the values in between lie from each other
+    fn outliers(
+        &self,
+        base: &SqlType,
+        excluded: &[&str],
+        inferencer: &TypeInferencer,
+    ) -> Vec<Sentinel> {
+        let Some(base_kind) = TypeSet::kind_of(base) else {
+            return Vec::new();
+        };
+        if !(TypeSet::NUMERIC | TypeSet::DATE).contains(base_kind) {
+            return Vec::new();
+        }
+
+        // Distinct values by position; the untracked range only bounds the others
+        let mut points: Vec<(f64, Option<(&str, usize)>)> = Vec::new();
+        for group in self.kinds.values() {
+            for (value, (count, _)) in &group.values {
+                if excluded.contains(&value.as_str()) {
+                    continue;
+                }
+                if let Some(at) = position(value, base_kind, inferencer) {
+                    points.push((at, Some((value.as_str(), *count))));
+                }
+            }
+            if let Some(untracked) = group.untracked.filter(|u| u.low <= u.high) {
+                points.push((untracked.low, None));
+                points.push((untracked.high, None));
+            }
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let n = points.len();
+        if n < 4 {
+            return Vec::new();
+        }
+        let span = points[n - 2].0 - points[1].0;
+        if span <= 0.0 {
+            return Vec::new();
+        }
+
+        let mut outliers = Vec::new();
+        let ends = [
+            (points[0], points[1].0 - points[0].0, "below"),
+            (points[n - 1], points[n - 1].0 - points[n - 2].0, "above"),
+        ];
+        for ((_, value), gap, side) in ends {
+            if let Some((value, count)) = value {
+                if count >= MIN_OUTLIER_COUNT && gap > span {
+                    outliers.push(Sentinel {
+                        value: value.to_string(),
+                        count,
+                        reason: format!("repeats far {} the other values", side),
+                    });
+                }
+            }
+        }
+        outliers
+    }
+}
+
+/// Where a number or date lies on a line, dates as days from the common era
+fn position(value: &str, kind: TypeSet, inferencer: &TypeInferencer) -> Option<f64> {
+    if kind == TypeSet::DATE {
+        inferencer
+            .parse_date(value)
+            .map(|date| date.num_days_from_ce() as f64)
+    } else {
+        value.parse::<f64>().ok().filter(|n| n.is_finite())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentinels(values: &[&str]) -> (Vec<Sentinel>, Option<SqlType>) {
+        let inferencer = TypeInferencer::new();
+        let mut profile = ValueProfile::default();
+        for value in values {
+            profile.record(value, inferencer.candidate_types(value), &inferencer);
+        }
+        profile.sentinels(&PromotionLattice::default(), &inferencer)
+    }
+
+    #[test]
+    fn test_token_forcing_varchar() {
+        let (found, without) = sentinels(&["10", "250", "N/A", "37", "N/A", "1200"]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].value, "N/A");
+        assert_eq!(found[0].count, 2);
+        assert_eq!(found[0].reason, "turns SMALLINT into VARCHAR(4)");
+        assert_eq!(without, Some(SqlType::SmallInt));
+
+        // A mostly text column has no sentinels
+        let (found, _) = sentinels(&["alpha", "beta", "gamma", "42"]);
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_repeated_outliers() {
+        let (found, without) = sentinels(&[
+            "34", "-99999", "27", "45", "-99999", "61", "52", "-99999", "19",
+        ]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].value, "-99999");
+        assert_eq!(found[0].reason, "repeats far below the other values");
+        assert_eq!(without, Some(SqlType::SmallInt));
+
+        let (found, without) = sentinels(&[
+            "2024-01-05",
+            "9999-12-31",
+            "2023-11-20",
+            "2024-03-14",
+            "9999-12-31",
+            "2022-07-01",
+        ]);
+        assert_eq!(found[0].value, "9999-12-31");
+        assert_eq!(without, Some(SqlType::Date));
+
+        // A far value seen once is not a sentinel
+        let (found, _) = sentinels(&["34", "-99999", "27", "45", "61"]);
+        assert!(found.is_empty());
+    }
+}
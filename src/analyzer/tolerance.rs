@@ -0,0 +1,136 @@
+use crate::analyzer::lattice::PromotionLattice;
+use crate::analyzer::type_set::{TypeSet, TypeSize};
+use crate::types::SqlType;
+
+/// Most values a column may reject under `--tolerance`; past this the rows are
+/// no longer kept and the column is widened as usual
+pub const MAX_TOLERATED_REJECTS: usize = 10_000;
+
+/// Kinds a column can keep while rejecting values outside them; a value in the
+/// same family, such as a larger integer, widens the type instead
+const FAMILIES: [TypeSet; 6] = [
+    TypeSet::BOOLEAN,
+    TypeSet::NUMERIC,
+    TypeSet::DATE,
+    TypeSet::TIME,
+    TypeSet::TIMESTAMPS,
+    TypeSet::JSON,
+];
+
+/// The values of one family and the rows of those outside it
+#[derive(Debug, Clone)]
+struct Family {
+    count: usize,
+    candidates: TypeSet,
+    observed: TypeSet,
+    size: TypeSize,
+    /// Row and value of each value outside the family; `None` once there are
+    /// more than `MAX_TOLERATED_REJECTS`
+    outsiders: Option<Vec<(usize, String)>>,
+}
+
+/// The type each family of values would give a column, for keeping a narrow
+/// type when only a few values disagree with it
+#[derive(Debug, Clone)]
+pub struct FamilyTally {
+    families: Vec<Family>,
+}
+
+impl Default for FamilyTally {
+    fn default() -> Self {
+        let family = Family {
+            count: 0,
+            candidates: TypeSet::ALL,
+            observed: TypeSet::EMPTY,
+            size: TypeSize::default(),
+            outsiders: Some(Vec::new()),
+        };
+        FamilyTally {
+            families: vec![family; FAMILIES.len()],
+        }
+    }
+}
+
+impl FamilyTally {
+    pub fn record(&mut self, row: usize, value: &str, types: TypeSet) {
+        let kind = types.narrowest().unwrap_or(TypeSet::VARCHAR);
+        for (family, members) in self.families.iter_mut().zip(FAMILIES) {
+            if (types & members).is_empty() {
+                if let Some(outsiders) = &mut family.outsiders {
+                    if outsiders.len() < MAX_TOLERATED_REJECTS {
+                        outsiders.push((row, value.to_string()));
+                    } else {
+                        family.outsiders = None;
+                    }
+                }
+            } else {
+                family.count += 1;
+                family.candidates = family.candidates & types;
+                family.observed |= kind;
+                family.size.widen(value);
+            }
+        }
+    }
+
+    /// The type of the family rejecting the fewest values, if it rejects at
+    /// most `allowed`, with the row and value of each rejected one
+    pub fn tolerated_type(
+        &self,
+        allowed: usize,
+        lattice: &PromotionLattice,
+    ) -> Option<(SqlType, &[(usize, String)])> {
+        self.families
+            .iter()
+            .filter(|family| family.count > 0)
+            .filter_map(|family| {
+                let outsiders = family.outsiders.as_deref()?;
+                if outsiders.len() > allowed {
+                    return None;
+                }
+                let (joined, _) = lattice.join_all(family.observed);
+                let kind = if family.candidates.contains(joined) {
+                    joined
+                } else {
+                    family.candidates.narrowest()?
+                };
+                (kind != TypeSet::VARCHAR).then(|| (kind.sql_type(family.size), outsiders))
+            })
+            .min_by_key(|(_, outsiders)| outsiders.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::patterns::TypeInferencer;
+
+    fn tally(values: &[&str]) -> FamilyTally {
+        let inferencer = TypeInferencer::new();
+        let mut tally = FamilyTally::default();
+        for (i, value) in values.iter().enumerate() {
+            tally.record(i + 1, value, inferencer.candidate_types(value));
+        }
+        tally
+    }
+
+    #[test]
+    fn test_tolerated_type() {
+        let lattice = PromotionLattice::default();
+        let values = ["12", "40000", "abc", "7", "1.5", "n/a", "3"];
+        let tally = tally(&values);
+
+        // Larger and fractional numbers widen the type; text is rejected
+        let (sql_type, rejects) = tally.tolerated_type(2, &lattice).unwrap();
+        assert_eq!(sql_type, SqlType::DoublePrecision);
+        assert_eq!(
+            rejects,
+            &[(3, "abc".to_string()), (6, "n/a".to_string())][..]
+        );
+        assert!(tally.tolerated_type(1, &lattice).is_none());
+
+        let tally = self::tally(&["2024-01-05", "2024-02-29", "TBD"]);
+        let (sql_type, rejects) = tally.tolerated_type(1, &lattice).unwrap();
+        assert_eq!(sql_type, SqlType::Date);
+        assert_eq!(rejects.len(), 1);
+    }
+}
@@ -0,0 +1,263 @@
+use crate::analyzer::patterns::decimal_digits;
+use crate::types::SqlType;
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitOrAssign, Sub};
+
+/// Value sizes that parameterise the VARCHAR and DECIMAL kinds
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TypeSize {
+    pub max_length: usize,
+    /// Most integer digits of any exact numeric value
+    pub int_digits: usize,
+    /// Most fractional digits of any exact numeric value
+    pub frac_digits: usize,
+}
+
+impl TypeSize {
+    pub fn text(max_length: usize) -> Self {
+        TypeSize {
+            max_length,
+            ..TypeSize::default()
+        }
+    }
+
+    /// Grow to hold `value` as text and, if it is a number, as a DECIMAL
+    pub fn widen(&mut self, value: &str) {
+        self.max_length = self.max_length.max(value.len());
+        if let Some((int_digits, frac_digits)) = decimal_digits(value) {
+            self.int_digits = self.int_digits.max(int_digits);
+            self.frac_digits = self.frac_digits.max(frac_digits);
+        }
+    }
+
+    /// Grow to hold everything `other` holds
+    pub fn merge(&mut self, other: TypeSize) {
+        self.max_length = self.max_length.max(other.max_length);
+        self.int_digits = self.int_digits.max(other.int_digits);
+        self.frac_digits = self.frac_digits.max(other.frac_digits);
+    }
+}
+
+/// A set of candidate SQL types, one bit per kind, ordered from narrowest to widest.
+///
+/// Each value gets the set of types it can be loaded as; a column keeps the
+/// intersection over all its values, so any type left in the set accepts them all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TypeSet(u16);
+
+const NAMES: [&str; 13] = [
+    "BOOLEAN",
+    "TINYINT",
+    "SMALLINT",
+    "INTEGER",
+    "BIGINT",
+    "DECIMAL",
+    "DOUBLE PRECISION",
+    "DATE",
+    "TIME",
+    "DATETIME",
+    "TIMESTAMPTZ",
+    "JSON",
+    "VARCHAR",
+];
+
+impl TypeSet {
+    pub const EMPTY: TypeSet = TypeSet(0);
+    pub const BOOLEAN: TypeSet = TypeSet(1 << 0);
+    pub const TINYINT: TypeSet = TypeSet(1 << 1);
+    pub const SMALLINT: TypeSet = TypeSet(1 << 2);
+    pub const INTEGER: TypeSet = TypeSet(1 << 3);
+    pub const BIGINT: TypeSet = TypeSet(1 << 4);
+    pub const DECIMAL: TypeSet = TypeSet(1 << 5);
+    pub const DOUBLE: TypeSet = TypeSet(1 << 6);
+    pub const DATE: TypeSet = TypeSet(1 << 7);
+    pub const TIME: TypeSet = TypeSet(1 << 8);
+    pub const DATETIME: TypeSet = TypeSet(1 << 9);
+    pub const DATETIMETZ: TypeSet = TypeSet(1 << 10);
+    pub const JSON: TypeSet = TypeSet(1 << 11);
+    pub const VARCHAR: TypeSet = TypeSet(1 << 12);
+    pub const ALL: TypeSet = TypeSet((1 << NAMES.len()) - 1);
+    pub const NUMERIC: TypeSet = TypeSet(
+        TypeSet::TINYINT.0
+            | TypeSet::SMALLINT.0
+            | TypeSet::INTEGER.0
+            | TypeSet::BIGINT.0
+            | TypeSet::DECIMAL.0
+            | TypeSet::DOUBLE.0,
+    );
+    /// Timestamps with or without a time zone
+    pub const TIMESTAMPS: TypeSet = TypeSet(TypeSet::DATETIME.0 | TypeSet::DATETIMETZ.0);
+
+    /// Kinds never inferred for a value on their own, only reached through a
+    /// promotion rule
+    pub const PROMOTION_ONLY: TypeSet = TypeSet::TINYINT;
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, other: TypeSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Each kind in the set, narrowest first
+    pub fn kinds(self) -> impl Iterator<Item = TypeSet> {
+        (0..NAMES.len())
+            .map(|bit| TypeSet(1 << bit))
+            .filter(move |kind| self.contains(*kind))
+    }
+
+    /// The narrowest kind in the set, other than the promotion-only kinds
+    pub fn narrowest(self) -> Option<TypeSet> {
+        let inferable = self - TypeSet::PROMOTION_ONLY;
+        (!inferable.is_empty()).then(|| TypeSet(inferable.0 & inferable.0.wrapping_neg()))
+    }
+
+    /// The `SqlType` of the narrowest kind
+    pub fn narrowest_type(self, size: TypeSize) -> SqlType {
+        self.narrowest().unwrap_or(TypeSet::VARCHAR).sql_type(size)
+    }
+
+    /// The `SqlType` of a single kind, with VARCHAR and DECIMAL sized by `size`
+    pub fn sql_type(self, size: TypeSize) -> SqlType {
+        match self {
+            TypeSet::BOOLEAN => SqlType::Boolean,
+            TypeSet::TINYINT => SqlType::TinyInt,
+            TypeSet::SMALLINT => SqlType::SmallInt,
+            TypeSet::INTEGER => SqlType::Integer,
+            TypeSet::BIGINT => SqlType::BigInt,
+            TypeSet::DECIMAL => {
+                let scale = size.frac_digits as u16;
+                SqlType::Decimal {
+                    precision: (size.int_digits as u16 + scale).max(1),
+                    scale,
+                }
+            }
+            TypeSet::DOUBLE => SqlType::DoublePrecision,
+            TypeSet::DATE => SqlType::Date,
+            TypeSet::TIME => SqlType::Time,
+            TypeSet::DATETIME => SqlType::DateTime,
+            TypeSet::DATETIMETZ => SqlType::DateTimeTz,
+            TypeSet::JSON => SqlType::Json,
+            _ => SqlType::Varchar(Some(size.max_length)),
+        }
+    }
+
+    /// A single kind by its name, e.g. `DECIMAL`, or by any `SqlType` name
+    pub fn from_name(name: &str) -> Option<TypeSet> {
+        match NAMES
+            .iter()
+            .position(|n| n.eq_ignore_ascii_case(name.trim()))
+        {
+            Some(bit) => Some(TypeSet(1 << bit)),
+            None => name.parse().ok().and_then(|t| TypeSet::kind_of(&t)),
+        }
+    }
+
+    /// The kind of a `SqlType`
+    pub fn kind_of(sql_type: &SqlType) -> Option<TypeSet> {
+        match sql_type {
+            SqlType::Boolean => Some(TypeSet::BOOLEAN),
+            SqlType::TinyInt => Some(TypeSet::TINYINT),
+            SqlType::SmallInt => Some(TypeSet::SMALLINT),
+            SqlType::Integer => Some(TypeSet::INTEGER),
+            SqlType::BigInt => Some(TypeSet::BIGINT),
+            SqlType::Decimal { .. } => Some(TypeSet::DECIMAL),
+            SqlType::DoublePrecision => Some(TypeSet::DOUBLE),
+            SqlType::Date => Some(TypeSet::DATE),
+            SqlType::Time => Some(TypeSet::TIME),
+            SqlType::DateTime => Some(TypeSet::DATETIME),
+            SqlType::DateTimeTz => Some(TypeSet::DATETIMETZ),
+            SqlType::Json => Some(TypeSet::JSON),
+            SqlType::Varchar(_) => Some(TypeSet::VARCHAR),
+        }
+    }
+}
+
+impl BitOr for TypeSet {
+    type Output = TypeSet;
+
+    fn bitor(self, rhs: TypeSet) -> TypeSet {
+        TypeSet(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for TypeSet {
+    fn bitor_assign(&mut self, rhs: TypeSet) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for TypeSet {
+    type Output = TypeSet;
+
+    fn bitand(self, rhs: TypeSet) -> TypeSet {
+        TypeSet(self.0 & rhs.0)
+    }
+}
+
+impl Sub for TypeSet {
+    type Output = TypeSet;
+
+    fn sub(self, rhs: TypeSet) -> TypeSet {
+        TypeSet(self.0 & !rhs.0)
+    }
+}
+
+impl fmt::Display for TypeSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = NAMES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_narrowest_and_display() {
+        let numbers = TypeSet::SMALLINT | TypeSet::INTEGER | TypeSet::DOUBLE | TypeSet::VARCHAR;
+        assert_eq!(numbers.narrowest(), Some(TypeSet::SMALLINT));
+        assert_eq!(numbers.narrowest_type(TypeSize::text(3)), SqlType::SmallInt);
+        assert_eq!(
+            (numbers - TypeSet::SMALLINT).narrowest_type(TypeSize::text(3)),
+            SqlType::Integer
+        );
+        assert_eq!(
+            TypeSet::VARCHAR.narrowest_type(TypeSize::text(3)),
+            SqlType::Varchar(Some(3))
+        );
+        assert_eq!(TypeSet::EMPTY.narrowest(), None);
+        assert_eq!(
+            (TypeSet::TINYINT | TypeSet::SMALLINT).narrowest(),
+            Some(TypeSet::SMALLINT)
+        );
+
+        assert_eq!(
+            (TypeSet::BOOLEAN | TypeSet::DATETIME).to_string(),
+            "BOOLEAN, DATETIME"
+        );
+        assert!(TypeSet::ALL.contains(numbers));
+
+        let money = TypeSize {
+            max_length: 8,
+            int_digits: 5,
+            frac_digits: 2,
+        };
+        assert_eq!(
+            TypeSet::DECIMAL.sql_type(money),
+            SqlType::Decimal {
+                precision: 7,
+                scale: 2
+            }
+        );
+        assert_eq!(TypeSet::from_name("decimal"), Some(TypeSet::DECIMAL));
+        assert_eq!(TypeSet::from_name("timestamp"), Some(TypeSet::DATETIME));
+    }
+}
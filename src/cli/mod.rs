@@ -1,5 +1,5 @@
 use clap::{Parser, Subcommand, ValueEnum};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -15,9 +15,13 @@ pub enum Commands {
     Diagnose(DiagnoseArgs),
 }
 
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 pub struct ParseArgs {
-    #[arg(short, long, help = "Input file path (default: stdin)")]
+    #[arg(
+        short,
+        long,
+        help = "Input file path, delimited text or .xlsx/.xls/.ods workbook (default: stdin)"
+    )]
     pub input: Option<PathBuf>,
 
     #[arg(short, long, help = "Output file path (default: stdout)")]
@@ -44,6 +48,28 @@ pub struct ParseArgs {
     #[arg(long, default_value = "", help = "Value to transform TO null")]
     pub tnull: String,
 
+    #[arg(
+        long,
+        help = "In typed output, treat only --fnull values as NULL, not the default '', NULL and null"
+    )]
+    pub no_default_nulls: bool,
+
+    #[arg(
+        long,
+        value_name = "COLUMN=VALUE",
+        value_parser = parse_column_value,
+        help = "Value to treat as NULL in one column only, in typed output"
+    )]
+    pub fnull_column: Vec<(String, String)>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "empty",
+        help = "In typed output, read whitespace-only values as empty strings, always as NULL, or as values"
+    )]
+    pub blank_values: BlankPolicy,
+
     #[arg(long, help = "File to write bad rows to")]
     pub badfile: Option<PathBuf>,
 
@@ -57,6 +83,26 @@ pub struct ParseArgs {
     #[arg(long, default_value = "utf-8", help = "Input file encoding")]
     pub encoding: String,
 
+    #[arg(long, help = "Worksheet name or 1-based index for workbook input")]
+    pub sheet: Option<String>,
+
+    #[arg(long, help = "Cell range for workbook input, e.g. A1:F100")]
+    pub range: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Input format (default: from the file extension, else csv)"
+    )]
+    pub input_format: Option<InputFormat>,
+
+    #[arg(
+        long,
+        default_value = "_",
+        help = "Separator joining nested JSON keys into column names"
+    )]
+    pub flatten_separator: String,
+
     #[arg(short = 'H', long, help = "File does not start with column headers")]
     pub noheader: bool,
 
@@ -72,11 +118,46 @@ pub struct ParseArgs {
         help = "Character to substitute for intrafield newlines"
     )]
     pub sub_newline: String,
+
+    #[arg(long, value_enum, default_value = "csv", help = "Output format")]
+    pub format: OutputFormat,
+
+    #[arg(long, help = "Table name for SQL output (default: input file name)")]
+    pub table: Option<String>,
+
+    #[arg(
+        long,
+        default_value = "500",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Rows per INSERT statement for SQL output"
+    )]
+    pub batch_size: u64,
+
+    #[arg(long, value_enum, default_value = "postgres", help = "Target database")]
+    pub database: DatabaseType,
+
+    #[arg(long, help = "Database configuration file (JSON format)")]
+    pub database_config: Option<PathBuf>,
+
+    #[arg(long, help = "Schema file from 'describe --json' for typed output")]
+    pub schema: Option<PathBuf>,
+
+    #[arg(
+        long,
+        default_value = "65536",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Rows per row group for Parquet output"
+    )]
+    pub row_group_size: u64,
 }
 
 #[derive(Parser, Debug)]
 pub struct DescribeArgs {
-    #[arg(short, long, help = "Input file path (default: stdin)")]
+    #[arg(
+        short,
+        long,
+        help = "Input file path, delimited text, workbook, Parquet or Arrow IPC (default: stdin)"
+    )]
     pub input: Option<PathBuf>,
 
     #[arg(short, long, default_value = ",", help = "Field delimiter")]
@@ -97,12 +178,25 @@ pub struct DescribeArgs {
     #[arg(long, help = "Generate DDL statement")]
     pub ddl: bool,
 
+    #[arg(
+        long,
+        conflicts_with = "ddl",
+        help = "Output the analysis as a JSON schema (usable with 'parse --schema')"
+    )]
+    pub json: bool,
+
     #[arg(long, value_enum, default_value = "postgres", help = "Target database")]
     pub database: DatabaseType,
 
     #[arg(long, help = "Database configuration file (JSON format)")]
     pub database_config: Option<PathBuf>,
 
+    #[arg(
+        long,
+        help = "Per-column type, NOT NULL, date format, null token and name overrides (JSON or TOML)"
+    )]
+    pub overrides: Option<PathBuf>,
+
     #[arg(long, help = "Date format string")]
     pub fdate: Option<String>,
 
@@ -115,15 +209,124 @@ pub struct DescribeArgs {
     #[arg(long, help = "Values to treat as NULL")]
     pub fnull: Vec<String>,
 
+    #[arg(
+        long,
+        help = "Treat only --fnull values as NULL, not the default '', NULL and null"
+    )]
+    pub no_default_nulls: bool,
+
+    #[arg(
+        long,
+        value_name = "COLUMN=VALUE",
+        value_parser = parse_column_value,
+        help = "Value to treat as NULL in one column only"
+    )]
+    pub fnull_column: Vec<(String, String)>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "empty",
+        help = "Read whitespace-only values as empty strings, always as NULL, or as values"
+    )]
+    pub blank_values: BlankPolicy,
+
     #[arg(long, default_value = "1", help = "TRUE value for boolean detection")]
     pub ftrue: String,
 
     #[arg(long, default_value = "0", help = "FALSE value for boolean detection")]
     pub ffalse: String,
 
+    #[arg(
+        long,
+        value_enum,
+        default_value = "float",
+        help = "Infer fixed-point numbers as DOUBLE PRECISION (float) or DECIMAL(p,s) (exact)"
+    )]
+    pub decimal_mode: DecimalMode,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "leading-zeros",
+        help = "Keep number-like identifiers as text: those with leading zeros or a + sign, also fixed-width ones, or never"
+    )]
+    pub numeric_ids_as_text: NumericIdPolicy,
+
+    #[arg(
+        long,
+        value_name = "PCT%|COUNT",
+        value_parser = parse_tolerance,
+        help = "Keep a column's type when at most this many values, or percent of them, disagree, and list those as expected rejects"
+    )]
+    pub tolerance: Option<Tolerance>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "1000",
+        help = "Count up to N distinct values per column exactly, then estimate them; 0 always estimates"
+    )]
+    pub exact_distinct: usize,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Show each column's N most frequent values and a histogram of numeric and temporal columns"
+    )]
+    pub top: Option<usize>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "Constraints to add to the DDL, comma-separated"
+    )]
+    pub constraints: Vec<ConstraintKind>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "20",
+        help = "Most distinct values a text column may have to get a categorical constraint"
+    )]
+    pub max_categories: usize,
+
+    #[arg(
+        long,
+        help = "Declare categorical columns as PostgreSQL enum types (CREATE TYPE ... AS ENUM) instead of CHECK constraints"
+    )]
+    pub enum_types: bool,
+
     #[arg(long, default_value = "utf-8", help = "Input file encoding")]
     pub encoding: String,
 
+    #[arg(long, help = "Worksheet name or 1-based index for workbook input")]
+    pub sheet: Option<String>,
+
+    #[arg(long, help = "Cell range for workbook input, e.g. A1:F100")]
+    pub range: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Input format (default: from the file extension, else csv)"
+    )]
+    pub input_format: Option<InputFormat>,
+
+    #[arg(
+        long,
+        default_value = "_",
+        help = "Separator joining nested JSON keys into column names"
+    )]
+    pub flatten_separator: String,
+
+    #[arg(
+        long,
+        help = "Scan Parquet/Arrow data for string lengths and null counts"
+    )]
+    pub scan: bool,
+
     #[arg(short = 'H', long, help = "File does not start with column headers")]
     pub noheader: bool,
 
@@ -143,7 +346,11 @@ pub struct DescribeArgs {
 
 #[derive(Parser, Debug)]
 pub struct DiagnoseArgs {
-    #[arg(short, long, help = "Input file path (default: stdin)")]
+    #[arg(
+        short,
+        long,
+        help = "Input file path, delimited text or .xlsx/.xls/.ods workbook (default: stdin)"
+    )]
     pub input: Option<PathBuf>,
 
     #[arg(short, long, default_value = ",", help = "Field delimiter")]
@@ -177,6 +384,12 @@ pub struct DiagnoseArgs {
     #[arg(long, default_value = "utf-8", help = "Input file encoding")]
     pub encoding: String,
 
+    #[arg(long, help = "Worksheet name or 1-based index for workbook input")]
+    pub sheet: Option<String>,
+
+    #[arg(long, help = "Cell range for workbook input, e.g. A1:F100")]
+    pub range: Option<String>,
+
     #[arg(short = 'H', long, help = "File does not start with column headers")]
     pub noheader: bool,
 
@@ -211,6 +424,132 @@ impl QuoteStyle {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum InputFormat {
+    Csv,
+    Jsonl,
+    /// Excel or OpenDocument workbook, only detected from the file extension
+    #[value(skip)]
+    Workbook,
+    Parquet,
+    /// Arrow IPC file or stream (Feather v2)
+    Arrow,
+}
+
+impl InputFormat {
+    /// Use the explicit `--input-format` if given, otherwise detect it from the path
+    pub fn resolve(explicit: Option<InputFormat>, path: Option<&Path>) -> InputFormat {
+        if let Some(format) = explicit {
+            return format;
+        }
+
+        let extension = path
+            .and_then(|p| p.extension())
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("jsonl" | "ndjson") => InputFormat::Jsonl,
+            Some("xlsx" | "xlsm" | "xlsb" | "xlam" | "xls" | "ods") => InputFormat::Workbook,
+            Some("parquet" | "pq") => InputFormat::Parquet,
+            Some("arrow" | "arrows" | "feather" | "ipc") => InputFormat::Arrow,
+            _ => InputFormat::Csv,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum DecimalMode {
+    Float,
+    Exact,
+}
+
+/// Which number-like values, such as zip codes and account numbers, are kept as text
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum NumericIdPolicy {
+    /// Infer every number-like column as numeric
+    Never,
+    /// Values whose leading zeros or `+` sign a number would drop
+    #[default]
+    LeadingZeros,
+    /// Also columns whose values all have the same number of digits
+    FixedWidth,
+}
+
+/// How values made only of spaces and tabs are analyzed
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum BlankPolicy {
+    /// Trimmed to an empty string, so NULL when `''` is a null token
+    #[default]
+    Empty,
+    /// Always NULL
+    Null,
+    /// Kept as text, never NULL
+    Value,
+}
+
+/// Constraints `describe --ddl` can derive from the data
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ConstraintKind {
+    /// Limit low-cardinality text columns to their values, with CHECK or ENUM
+    Categorical,
+    /// Add a PRIMARY KEY and UNIQUE constraints for columns, or pairs of
+    /// columns, that are present and distinct in every row
+    Keys,
+}
+
+/// How many values of a column may disagree with its type before it is widened
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tolerance {
+    Count(usize),
+    Percent(f64),
+}
+
+impl Tolerance {
+    /// The number of disagreeing values allowed among `values`
+    pub fn allowed(self, values: usize) -> usize {
+        match self {
+            Tolerance::Count(count) => count,
+            Tolerance::Percent(percent) => (values as f64 * percent / 100.0).floor() as usize,
+        }
+    }
+}
+
+/// Read `0.1%` as a percentage and `5` as a count
+fn parse_tolerance(arg: &str) -> Result<Tolerance, String> {
+    match arg.trim().strip_suffix('%') {
+        Some(percent) => match percent.trim().parse::<f64>() {
+            Ok(p) if (0.0..=100.0).contains(&p) => Ok(Tolerance::Percent(p)),
+            _ => Err(format!(
+                "expected a percentage from 0% to 100%, got '{}'",
+                arg
+            )),
+        },
+        None => arg.trim().parse().map(Tolerance::Count).map_err(|_| {
+            format!(
+                "expected a count or a percentage such as 0.1%, got '{}'",
+                arg
+            )
+        }),
+    }
+}
+
+/// Split a `COLUMN=VALUE` argument at the first `=`
+fn parse_column_value(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(column, value)| (column.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected COLUMN=VALUE, got '{}'", arg))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    Csv,
+    Tsv,
+    Jsonl,
+    SqlInsert,
+    Parquet,
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum)]
 pub enum DatabaseType {
     Postgres,
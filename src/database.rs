@@ -1,4 +1,6 @@
-use crate::types::SqlType;
+use crate::analyzer::lattice::{PromotionLattice, PromotionRule};
+use crate::cli::DatabaseType;
+use crate::types::{ColumnStats, SqlType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -8,6 +10,68 @@ pub trait DatabaseDialect {
     fn name(&self) -> &'static str;
     fn map_type(&self, sql_type: &SqlType) -> String;
     fn supports_feature(&self, feature: DatabaseFeature) -> bool;
+
+    /// DDL type for a column, adding fractional-seconds precision when it is known
+    fn column_type(&self, stats: &ColumnStats) -> String {
+        let data_type = self.map_type(&stats.sql_type);
+        match (&stats.sql_type, stats.time_precision) {
+            (SqlType::Time | SqlType::DateTime | SqlType::DateTimeTz, Some(precision))
+                if self.supports_feature(DatabaseFeature::FractionalSeconds) =>
+            {
+                // Microseconds are the finest precision the built-in dialects store
+                format!("{}({})", data_type, precision.min(6))
+            }
+            _ => data_type,
+        }
+    }
+
+    /// Why `map_type` cannot fully represent a type, for types it maps lossily
+    fn type_warning(&self, _sql_type: &SqlType) -> Option<&'static str> {
+        None
+    }
+
+    /// How the types of mixed values are joined during inference
+    fn promotion_lattice(&self) -> PromotionLattice {
+        PromotionLattice::default()
+    }
+
+    /// Most digits a DECIMAL column can declare; longer numbers are inferred as text
+    fn max_decimal_precision(&self) -> usize {
+        38
+    }
+
+    /// Quote an identifier when it would not be valid unquoted
+    fn quote_identifier(&self, ident: &str) -> String {
+        if needs_quoting(ident) {
+            format!("\"{}\"", ident.replace('"', "\"\""))
+        } else {
+            ident.to_string()
+        }
+    }
+
+    /// Render a value as a string literal
+    fn quote_string(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+const RESERVED_WORDS: &str = "ALL AND AS ASC BETWEEN BY CASE CHECK COLUMN CONSTRAINT CREATE \
+    DEFAULT DESC DISTINCT ELSE END FOREIGN FROM GRANT GROUP HAVING IN INDEX INSERT INTO IS JOIN \
+    KEY LIKE LIMIT NOT NULL OFFSET ON OR ORDER PRIMARY REFERENCES SELECT TABLE THEN TO UNION \
+    UNIQUE USER VALUES WHEN WHERE";
+
+fn needs_quoting(ident: &str) -> bool {
+    let mut chars = ident.chars();
+    let valid_start = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    let valid_rest = chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    !valid_start
+        || !valid_rest
+        || RESERVED_WORDS
+            .split_whitespace()
+            .any(|word| word.eq_ignore_ascii_case(ident))
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -16,6 +80,13 @@ pub enum DatabaseFeature {
     BooleanType,
     DoublePrecision,
     TimestampType,
+    FractionalSeconds,
+    /// Column-level `CHECK (col IN (...))` constraints
+    CheckConstraints,
+    /// An inline `ENUM('a', 'b')` column type
+    InlineEnum,
+    /// Named enum types declared with `CREATE TYPE ... AS ENUM`
+    EnumTypes,
 }
 
 pub struct PostgreSQL;
@@ -30,13 +101,17 @@ impl DatabaseDialect for PostgreSQL {
     fn map_type(&self, sql_type: &SqlType) -> String {
         match sql_type {
             SqlType::Boolean => "BOOLEAN".to_string(),
-            SqlType::SmallInt => "SMALLINT".to_string(),
+            // No one-byte integer type
+            SqlType::TinyInt | SqlType::SmallInt => "SMALLINT".to_string(),
             SqlType::Integer => "INTEGER".to_string(),
             SqlType::BigInt => "BIGINT".to_string(),
+            SqlType::Decimal { precision, scale } => format!("NUMERIC({},{})", precision, scale),
             SqlType::DoublePrecision => "DOUBLE PRECISION".to_string(),
             SqlType::Date => "DATE".to_string(),
             SqlType::Time => "TIME".to_string(),
             SqlType::DateTime => "TIMESTAMP".to_string(),
+            SqlType::DateTimeTz => "TIMESTAMPTZ".to_string(),
+            SqlType::Json => "JSONB".to_string(),
             SqlType::Varchar(Some(n)) => format!("VARCHAR({})", n),
             SqlType::Varchar(None) => "TEXT".to_string(),
         }
@@ -48,8 +123,17 @@ impl DatabaseDialect for PostgreSQL {
             DatabaseFeature::BooleanType => true,
             DatabaseFeature::DoublePrecision => true,
             DatabaseFeature::TimestampType => true,
+            DatabaseFeature::FractionalSeconds => true,
+            DatabaseFeature::CheckConstraints => true,
+            DatabaseFeature::InlineEnum => false,
+            DatabaseFeature::EnumTypes => true,
         }
     }
+
+    fn max_decimal_precision(&self) -> usize {
+        // NUMERIC accepts a declared precision of up to 1000 digits
+        1000
+    }
 }
 
 impl DatabaseDialect for MySQL {
@@ -60,13 +144,18 @@ impl DatabaseDialect for MySQL {
     fn map_type(&self, sql_type: &SqlType) -> String {
         match sql_type {
             SqlType::Boolean => "BOOLEAN".to_string(),
+            SqlType::TinyInt => "TINYINT".to_string(),
             SqlType::SmallInt => "SMALLINT".to_string(),
             SqlType::Integer => "INTEGER".to_string(),
             SqlType::BigInt => "BIGINT".to_string(),
+            SqlType::Decimal { precision, scale } => format!("DECIMAL({},{})", precision, scale),
             SqlType::DoublePrecision => "DOUBLE".to_string(),
             SqlType::Date => "DATE".to_string(),
             SqlType::Time => "TIME".to_string(),
             SqlType::DateTime => "DATETIME".to_string(),
+            // No zoned type; see type_warning
+            SqlType::DateTimeTz => "DATETIME".to_string(),
+            SqlType::Json => "JSON".to_string(),
             SqlType::Varchar(Some(n)) => format!("VARCHAR({})", n),
             SqlType::Varchar(None) => "TEXT".to_string(),
         }
@@ -78,8 +167,45 @@ impl DatabaseDialect for MySQL {
             DatabaseFeature::BooleanType => true,
             DatabaseFeature::DoublePrecision => false, // Uses DOUBLE instead
             DatabaseFeature::TimestampType => false, // Uses DATETIME instead
+            DatabaseFeature::FractionalSeconds => true,
+            DatabaseFeature::CheckConstraints => true, // Enforced since 8.0.16
+            DatabaseFeature::InlineEnum => true,
+            DatabaseFeature::EnumTypes => false,
+        }
+    }
+
+    fn promotion_lattice(&self) -> PromotionLattice {
+        // BOOLEAN is TINYINT(1), so flags mixed with small counts stay one byte
+        PromotionLattice::default()
+            .with_rules(mysql_promotions())
+            .expect("MySQL promotion rules are valid")
+    }
+
+    fn max_decimal_precision(&self) -> usize {
+        65
+    }
+
+    fn type_warning(&self, sql_type: &SqlType) -> Option<&'static str> {
+        match sql_type {
+            SqlType::DateTimeTz => {
+                Some("DATETIME drops the time zone offset; convert the values to UTC before loading")
+            }
+            _ => None,
+        }
+    }
+
+    fn quote_identifier(&self, ident: &str) -> String {
+        if needs_quoting(ident) {
+            format!("`{}`", ident.replace('`', "``"))
+        } else {
+            ident.to_string()
         }
     }
+
+    fn quote_string(&self, value: &str) -> String {
+        // Backslash is an escape character in MySQL string literals by default
+        format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+    }
 }
 
 impl DatabaseDialect for Netezza {
@@ -90,13 +216,19 @@ impl DatabaseDialect for Netezza {
     fn map_type(&self, sql_type: &SqlType) -> String {
         match sql_type {
             SqlType::Boolean => "BOOLEAN".to_string(),
+            SqlType::TinyInt => "BYTEINT".to_string(),
             SqlType::SmallInt => "SMALLINT".to_string(),
             SqlType::Integer => "INTEGER".to_string(),
             SqlType::BigInt => "BIGINT".to_string(),
+            // Inference keeps DECIMAL within max_decimal_precision, 38 digits here
+            SqlType::Decimal { precision, scale } => format!("NUMERIC({},{})", precision, scale),
             SqlType::DoublePrecision => "DOUBLE PRECISION".to_string(),
             SqlType::Date => "DATE".to_string(),
             SqlType::Time => "TIME".to_string(),
-            SqlType::DateTime => "TIMESTAMP".to_string(),
+            // TIMESTAMP WITH TIME ZONE is not available in Netezza tables
+            SqlType::DateTime | SqlType::DateTimeTz => "TIMESTAMP".to_string(),
+            // No native JSON type; store the document text
+            SqlType::Json => "VARCHAR(65535)".to_string(),
             SqlType::Varchar(Some(n)) => format!("VARCHAR({})", n),
             SqlType::Varchar(None) => "VARCHAR(65535)".to_string(),
         }
@@ -108,6 +240,10 @@ impl DatabaseDialect for Netezza {
             DatabaseFeature::BooleanType => true,
             DatabaseFeature::DoublePrecision => true,
             DatabaseFeature::TimestampType => true,
+            DatabaseFeature::FractionalSeconds => false, // TIMESTAMP is always microseconds
+            DatabaseFeature::CheckConstraints => true,
+            DatabaseFeature::InlineEnum => false,
+            DatabaseFeature::EnumTypes => false,
         }
     }
 }
@@ -119,6 +255,16 @@ pub struct DatabaseConfig {
     pub features: HashMap<String, bool>,
     pub default_varchar_length: Option<usize>,
     pub unlimited_varchar_type: String,
+    /// Promotion rules added to, or replacing, the default lattice
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub promotions: Vec<PromotionRule>,
+    /// Most digits a DECIMAL may declare, 38 when not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_decimal_precision: Option<usize>,
+}
+
+fn mysql_promotions() -> Vec<PromotionRule> {
+    vec![PromotionRule::new("BOOLEAN", "SMALLINT", "TINYINT")]
 }
 
 impl DatabaseConfig {
@@ -140,6 +286,8 @@ impl DatabaseConfig {
                 return Err(anyhow::anyhow!("Missing type mapping for: {}", required_type));
             }
         }
+
+        PromotionLattice::default().with_rules(self.promotions.clone())?;
         
         Ok(())
     }
@@ -170,6 +318,8 @@ impl DatabaseConfig {
             features: pg_features,
             default_varchar_length: None,
             unlimited_varchar_type: "TEXT".to_string(),
+            promotions: Vec::new(),
+            max_decimal_precision: Some(1000),
         });
 
         // MySQL config
@@ -191,6 +341,8 @@ impl DatabaseConfig {
             features: HashMap::new(),
             default_varchar_length: None,
             unlimited_varchar_type: "TEXT".to_string(),
+            promotions: mysql_promotions(),
+            max_decimal_precision: Some(65),
         });
 
         // Netezza config
@@ -212,6 +364,8 @@ impl DatabaseConfig {
             features: HashMap::new(),
             default_varchar_length: Some(65535),
             unlimited_varchar_type: "VARCHAR(65535)".to_string(),
+            promotions: Vec::new(),
+            max_decimal_precision: Some(38),
         });
 
         configs
@@ -243,13 +397,54 @@ impl DatabaseDialect for ConfigurableDialect {
     fn map_type(&self, sql_type: &SqlType) -> String {
         let type_key = match sql_type {
             SqlType::Boolean => "Boolean",
+            SqlType::TinyInt => {
+                // Optional; configs without it use their SMALLINT type
+                let mappings = &self.config.type_mappings;
+                return mappings
+                    .get("TinyInt")
+                    .or_else(|| mappings.get("SmallInt"))
+                    .cloned()
+                    .unwrap_or_else(|| "UNKNOWN_TinyInt".to_string());
+            }
             SqlType::SmallInt => "SmallInt", 
             SqlType::Integer => "Integer",
             SqlType::BigInt => "BigInt",
+            // A 128-bit integer holds any whole number of up to 38 digits
+            SqlType::Decimal { precision: 19..=38, scale: 0 }
+                if self.config.type_mappings.contains_key("HugeInt") =>
+            {
+                return self.config.type_mappings["HugeInt"].clone();
+            }
+            SqlType::Decimal { precision, scale } => {
+                let default_template = "NUMERIC({},{})".to_string();
+                let template = self.config.type_mappings.get("Decimal")
+                    .unwrap_or(&default_template);
+                return template
+                    .replacen("{}", &precision.to_string(), 1)
+                    .replacen("{}", &scale.to_string(), 1);
+            }
             SqlType::DoublePrecision => "DoublePrecision",
             SqlType::Date => "Date",
             SqlType::Time => "Time",
             SqlType::DateTime => "DateTime",
+            SqlType::DateTimeTz => {
+                // Optional; configs without it use their naive timestamp type
+                let mappings = &self.config.type_mappings;
+                return mappings
+                    .get("DateTimeTz")
+                    .or_else(|| mappings.get("DateTime"))
+                    .cloned()
+                    .unwrap_or_else(|| "UNKNOWN_DateTimeTz".to_string());
+            }
+            SqlType::Json => {
+                // Configs written before JSON support fall back to the unlimited text type
+                let mappings = &self.config.type_mappings;
+                return mappings
+                    .get("Json")
+                    .or_else(|| mappings.get("VarcharUnlimited"))
+                    .cloned()
+                    .unwrap_or_else(|| "UNKNOWN_Json".to_string());
+            }
             SqlType::Varchar(Some(n)) => {
                 let default_template = "VARCHAR({})".to_string();
                 let template = self.config.type_mappings.get("Varchar")
@@ -271,10 +466,25 @@ impl DatabaseDialect for ConfigurableDialect {
             DatabaseFeature::BooleanType => "boolean_type", 
             DatabaseFeature::DoublePrecision => "double_precision",
             DatabaseFeature::TimestampType => "timestamp_type",
+            DatabaseFeature::FractionalSeconds => "fractional_seconds",
+            DatabaseFeature::CheckConstraints => "check_constraints",
+            DatabaseFeature::InlineEnum => "inline_enum",
+            DatabaseFeature::EnumTypes => "enum_types",
         };
 
         self.config.features.get(feature_key).copied().unwrap_or(false)
     }
+
+    fn promotion_lattice(&self) -> PromotionLattice {
+        // Rules are checked when the config is loaded
+        PromotionLattice::default()
+            .with_rules(self.config.promotions.clone())
+            .unwrap_or_default()
+    }
+
+    fn max_decimal_precision(&self) -> usize {
+        self.config.max_decimal_precision.unwrap_or(38)
+    }
 }
 
 pub fn get_database_dialect(name: &str) -> anyhow::Result<Box<dyn DatabaseDialect>> {
@@ -291,6 +501,21 @@ pub fn get_database_dialect_from_config<P: AsRef<Path>>(config_path: P) -> anyho
     Ok(Box::new(dialect))
 }
 
+/// Pick the dialect for a command: a `--database-config` file wins over `--database`
+pub fn resolve_dialect(
+    database: &DatabaseType,
+    config_path: Option<&Path>,
+) -> anyhow::Result<Box<dyn DatabaseDialect>> {
+    match config_path {
+        Some(path) => get_database_dialect_from_config(path),
+        None => match database {
+            DatabaseType::Postgres => get_database_dialect("postgresql"),
+            DatabaseType::Mysql => get_database_dialect("mysql"),
+            DatabaseType::Netezza => get_database_dialect("netezza"),
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -384,6 +609,94 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_identifier_quoting() {
+        let pg = PostgreSQL;
+        assert_eq!(pg.quote_identifier("customer_id"), "customer_id");
+        assert_eq!(pg.quote_identifier("order"), "\"order\"");
+        assert_eq!(pg.quote_identifier("first name"), "\"first name\"");
+        assert_eq!(pg.quote_identifier("say \"hi\""), "\"say \"\"hi\"\"\"");
+
+        let mysql = MySQL;
+        assert_eq!(mysql.quote_identifier("Order"), "`Order`");
+        assert_eq!(mysql.quote_identifier("a`b"), "`a``b`");
+    }
+
+    #[test]
+    fn test_column_type_precision() {
+        let mut stats = ColumnStats::new("ts".to_string());
+        stats.sql_type = SqlType::DateTime;
+        stats.time_precision = Some(9);
+        assert_eq!(PostgreSQL.column_type(&stats), "TIMESTAMP(6)");
+        assert_eq!(Netezza.column_type(&stats), "TIMESTAMP");
+
+        stats.sql_type = SqlType::Decimal { precision: 40, scale: 2 };
+        assert_eq!(MySQL.column_type(&stats), "DECIMAL(40,2)");
+        assert_eq!(Netezza.column_type(&stats), "NUMERIC(40,2)");
+    }
+
+    #[test]
+    fn test_zoned_timestamp_mapping() {
+        assert_eq!(PostgreSQL.map_type(&SqlType::DateTimeTz), "TIMESTAMPTZ");
+        assert_eq!(MySQL.map_type(&SqlType::DateTimeTz), "DATETIME");
+        assert_eq!(Netezza.map_type(&SqlType::DateTimeTz), "TIMESTAMP");
+        assert!(MySQL.type_warning(&SqlType::DateTimeTz).is_some());
+        assert!(PostgreSQL.type_warning(&SqlType::DateTimeTz).is_none());
+
+        let mut config = DatabaseConfig::to_builtin_databases()["postgresql"].clone();
+        let dialect = ConfigurableDialect::new(config.clone());
+        assert_eq!(dialect.map_type(&SqlType::DateTimeTz), "TIMESTAMP");
+        config.type_mappings.insert("DateTimeTz".to_string(), "TIMESTAMPTZ".to_string());
+        let dialect = ConfigurableDialect::new(config);
+        assert_eq!(dialect.map_type(&SqlType::DateTimeTz), "TIMESTAMPTZ");
+    }
+
+    #[test]
+    fn test_wide_integer_types() {
+        assert_eq!(PostgreSQL.max_decimal_precision(), 1000);
+        assert_eq!(MySQL.max_decimal_precision(), 65);
+        assert_eq!(Netezza.max_decimal_precision(), 38);
+
+        let mut config = DatabaseConfig::to_builtin_databases()["netezza"].clone();
+        config.type_mappings.insert("HugeInt".to_string(), "HUGEINT".to_string());
+        config.max_decimal_precision = None;
+        let dialect = ConfigurableDialect::new(config);
+        assert_eq!(dialect.max_decimal_precision(), 38);
+        assert_eq!(dialect.map_type(&SqlType::Decimal { precision: 20, scale: 0 }), "HUGEINT");
+        assert_eq!(dialect.map_type(&SqlType::Decimal { precision: 20, scale: 2 }), "NUMERIC(20,2)");
+        assert_eq!(dialect.map_type(&SqlType::Decimal { precision: 12, scale: 0 }), "NUMERIC(12,0)");
+    }
+
+    #[test]
+    fn test_promotion_lattice_per_dialect() {
+        use crate::analyzer::type_set::TypeSet;
+
+        let flags_and_counts = TypeSet::BOOLEAN | TypeSet::SMALLINT;
+        assert_eq!(PostgreSQL.promotion_lattice().join_all(flags_and_counts).0, TypeSet::SMALLINT);
+        assert_eq!(MySQL.promotion_lattice().join_all(flags_and_counts).0, TypeSet::TINYINT);
+
+        let mut config = DatabaseConfig::to_builtin_databases()["postgresql"].clone();
+        config.promotions = vec![PromotionRule::new("DATE", "DATETIME", "VARCHAR")];
+        assert!(config.validate().is_ok());
+        let dialect = ConfigurableDialect::new(config.clone());
+        assert_eq!(
+            dialect.promotion_lattice().join_all(TypeSet::DATE | TypeSet::DATETIME).0,
+            TypeSet::VARCHAR
+        );
+
+        config.promotions = vec![PromotionRule::new("DATE", "UUID", "VARCHAR")];
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_string_literal_escaping() {
+        assert_eq!(PostgreSQL.quote_string("O'Brien"), "'O''Brien'");
+        assert_eq!(PostgreSQL.quote_string("C:\\temp"), "'C:\\temp'");
+        assert_eq!(MySQL.quote_string("O'Brien"), "'O''Brien'");
+        assert_eq!(MySQL.quote_string("C:\\temp"), "'C:\\\\temp'");
+        assert_eq!(Netezza.quote_string("it's"), "'it''s'");
+    }
+
     #[test]
     fn test_builtin_database_configs() {
         let configs = DatabaseConfig::to_builtin_databases();
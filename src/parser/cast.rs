@@ -0,0 +1,296 @@
+use crate::analyzer::nulls::NullPolicy;
+use crate::analyzer::patterns::{decimal_digits, TypeInferencer};
+use crate::types::{SqlType, TableSchema};
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use csv::StringRecord;
+
+/// A field value converted to the domain of its column's `SqlType`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedValue<'a> {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    /// An exact decimal, kept as its validated source text
+    Decimal(&'a str),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    DateTime(NaiveDateTime),
+    DateTimeTz(DateTime<FixedOffset>),
+    /// A JSON array or object, kept as its source text
+    Json(&'a str),
+    Text(&'a str),
+}
+
+impl TypedValue<'_> {
+    /// ISO 8601 text for temporal values, used by text-based output formats
+    pub fn temporal_text(&self) -> Option<String> {
+        match self {
+            TypedValue::Date(date) => Some(date.format("%Y-%m-%d").to_string()),
+            TypedValue::Time(time) => Some(time.format("%H:%M:%S%.f").to_string()),
+            TypedValue::DateTime(datetime) => {
+                Some(datetime.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+            }
+            TypedValue::DateTimeTz(datetime) => {
+                Some(datetime.format("%Y-%m-%d %H:%M:%S%.f%:z").to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Convert a raw field to its column type, returning a reason when it does not fit
+pub fn cast_value<'a>(
+    value: &'a str,
+    sql_type: &SqlType,
+    inferencer: &TypeInferencer,
+) -> Result<TypedValue<'a>, String> {
+    let trimmed = value.trim();
+
+    match sql_type {
+        SqlType::Boolean => inferencer
+            .parse_boolean(trimmed)
+            .map(TypedValue::Boolean)
+            .ok_or_else(|| format!("'{}' is not a valid {}", value, sql_type)),
+        SqlType::TinyInt | SqlType::SmallInt | SqlType::Integer | SqlType::BigInt => {
+            let (min, max) = match sql_type {
+                SqlType::TinyInt => (i8::MIN as i64, i8::MAX as i64),
+                SqlType::SmallInt => (i16::MIN as i64, i16::MAX as i64),
+                SqlType::Integer => (i32::MIN as i64, i32::MAX as i64),
+                _ => (i64::MIN, i64::MAX),
+            };
+            match trimmed.parse::<i64>() {
+                Ok(num) if (min..=max).contains(&num) => Ok(TypedValue::Integer(num)),
+                _ => Err(format!("'{}' is not a valid {}", value, sql_type)),
+            }
+        }
+        SqlType::Decimal { precision, scale } => match decimal_digits(trimmed) {
+            Some((int_digits, frac_digits))
+                if int_digits <= (precision - scale) as usize && frac_digits <= *scale as usize =>
+            {
+                Ok(TypedValue::Decimal(
+                    trimmed.strip_prefix('+').unwrap_or(trimmed),
+                ))
+            }
+            _ => Err(format!("'{}' is not a valid {}", value, sql_type)),
+        },
+        SqlType::DoublePrecision => match trimmed.parse::<f64>() {
+            Ok(num) if num.is_finite() => Ok(TypedValue::Float(num)),
+            _ => Err(format!("'{}' is not a valid {}", value, sql_type)),
+        },
+        // Sized like describe's max_length, which ignores surrounding whitespace
+        SqlType::Varchar(Some(size)) if trimmed.len() > *size => Err(format!(
+            "'{}' is {} bytes, longer than {}",
+            value,
+            trimmed.len(),
+            sql_type
+        )),
+        SqlType::Date => inferencer
+            .parse_date(trimmed)
+            .map(TypedValue::Date)
+            .ok_or_else(|| format!("'{}' is not a valid {}", value, sql_type)),
+        SqlType::Time => inferencer
+            .parse_time(trimmed)
+            .map(TypedValue::Time)
+            .ok_or_else(|| format!("'{}' is not a valid {}", value, sql_type)),
+        // A bare date is loaded as midnight, as describe allows DATE values in DATETIME
+        SqlType::DateTime => inferencer
+            .parse_datetime(trimmed)
+            .or_else(|| {
+                inferencer
+                    .parse_date(trimmed)
+                    .map(|d| d.and_time(NaiveTime::MIN))
+            })
+            .map(TypedValue::DateTime)
+            .ok_or_else(|| format!("'{}' is not a valid {}", value, sql_type)),
+        // A naive timestamp in a zoned column is taken to be UTC
+        SqlType::DateTimeTz => inferencer
+            .parse_datetime_tz(trimmed)
+            .or_else(|| {
+                inferencer
+                    .parse_datetime(trimmed)
+                    .map(|dt| dt.and_utc().fixed_offset())
+            })
+            .map(TypedValue::DateTimeTz)
+            .ok_or_else(|| format!("'{}' is not a valid {}", value, sql_type)),
+        SqlType::Json if inferencer.is_json(trimmed) => Ok(TypedValue::Json(trimmed)),
+        SqlType::Json => Err(format!("'{}' is not a valid {}", value, sql_type)),
+        SqlType::Varchar(_) => Ok(TypedValue::Text(value)),
+    }
+}
+
+/// Applies the null policy and, when a schema is given, column types to whole records
+pub struct RecordCaster {
+    schema: Option<TableSchema>,
+    /// Each column's type, with an inferencer reading its discovered date format
+    columns: Vec<(SqlType, TypeInferencer)>,
+    nulls: NullPolicy,
+    /// `nulls` with each column's own tokens applied
+    column_nulls: Vec<NullPolicy>,
+}
+
+impl RecordCaster {
+    pub fn new(schema: Option<TableSchema>, nulls: NullPolicy) -> Self {
+        RecordCaster {
+            schema,
+            columns: Vec::new(),
+            nulls,
+            column_nulls: Vec::new(),
+        }
+    }
+
+    pub fn schema(&self) -> Option<&TableSchema> {
+        self.schema.as_ref()
+    }
+
+    /// Resolve each header against the schema, failing if a column is missing
+    pub fn set_headers(&mut self, headers: &StringRecord) -> Result<()> {
+        self.column_nulls = headers.iter().map(|h| self.nulls.for_column(h)).collect();
+        if let Some(schema) = &self.schema {
+            self.columns = headers
+                .iter()
+                .map(|h| {
+                    let column = schema
+                        .column(h)
+                        .ok_or_else(|| anyhow::anyhow!("Column '{}' not found in schema", h))?;
+                    let inferencer =
+                        TypeInferencer::with_formats(column.date_format.clone(), None, None);
+                    Ok((column.sql_type.clone(), inferencer))
+                })
+                .collect::<Result<_>>()?;
+        }
+        Ok(())
+    }
+
+    pub fn cast_record<'a>(&self, record: &'a StringRecord) -> Result<Vec<TypedValue<'a>>, String> {
+        record
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let nulls = self.column_nulls.get(i).unwrap_or(&self.nulls);
+                if nulls.read(field).is_none() {
+                    return Ok(TypedValue::Null);
+                }
+                let field = nulls.unescape(field);
+                match self.columns.get(i) {
+                    Some((sql_type, inferencer)) => cast_value(field, sql_type, inferencer)
+                        .map_err(|reason| format!("Column {}: {}", i + 1, reason)),
+                    None => Ok(TypedValue::Text(field)),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cast_numbers() {
+        let inferencer = TypeInferencer::new();
+
+        assert_eq!(
+            cast_value(" 42 ", &SqlType::SmallInt, &inferencer),
+            Ok(TypedValue::Integer(42))
+        );
+        assert!(cast_value("40000", &SqlType::SmallInt, &inferencer).is_err());
+        assert_eq!(
+            cast_value("40000", &SqlType::Integer, &inferencer),
+            Ok(TypedValue::Integer(40000))
+        );
+        assert_eq!(
+            cast_value("2.5", &SqlType::DoublePrecision, &inferencer),
+            Ok(TypedValue::Float(2.5))
+        );
+        assert!(cast_value("NaN", &SqlType::DoublePrecision, &inferencer).is_err());
+        assert!(cast_value("abc", &SqlType::BigInt, &inferencer).is_err());
+
+        let money = SqlType::Decimal {
+            precision: 5,
+            scale: 2,
+        };
+        assert_eq!(
+            cast_value("+123.4", &money, &inferencer),
+            Ok(TypedValue::Decimal("123.4"))
+        );
+        assert!(cast_value("1234.5", &money, &inferencer).is_err());
+        assert!(cast_value("1.234", &money, &inferencer).is_err());
+    }
+
+    #[test]
+    fn test_cast_boolean_and_text() {
+        let inferencer = TypeInferencer::new();
+
+        assert_eq!(
+            cast_value("yes", &SqlType::Boolean, &inferencer),
+            Ok(TypedValue::Boolean(true))
+        );
+        assert_eq!(
+            cast_value("0", &SqlType::Boolean, &inferencer),
+            Ok(TypedValue::Boolean(false))
+        );
+        assert!(cast_value("maybe", &SqlType::Boolean, &inferencer).is_err());
+        assert_eq!(
+            cast_value("abc", &SqlType::Varchar(Some(3)), &inferencer),
+            Ok(TypedValue::Text("abc"))
+        );
+        assert!(cast_value("abcd", &SqlType::Varchar(Some(3)), &inferencer).is_err());
+        assert_eq!(
+            cast_value(" [1, 2] ", &SqlType::Json, &inferencer),
+            Ok(TypedValue::Json("[1, 2]"))
+        );
+        assert!(cast_value("[1, 2", &SqlType::Json, &inferencer).is_err());
+    }
+
+    #[test]
+    fn test_cast_temporal() {
+        let inferencer = TypeInferencer::new();
+
+        let date = cast_value("2024-01-31", &SqlType::Date, &inferencer).unwrap();
+        assert_eq!(date.temporal_text().unwrap(), "2024-01-31");
+        assert!(cast_value("2024-02-31", &SqlType::Date, &inferencer).is_err());
+
+        let time = cast_value("9:15:30", &SqlType::Time, &inferencer).unwrap();
+        assert_eq!(time.temporal_text().unwrap(), "09:15:30");
+
+        let datetime = cast_value("2024-01-31 23:59:59", &SqlType::DateTime, &inferencer).unwrap();
+        assert!(matches!(datetime, TypedValue::DateTime(_)));
+        let midnight = cast_value("2024-01-31", &SqlType::DateTime, &inferencer).unwrap();
+        assert_eq!(midnight.temporal_text().unwrap(), "2024-01-31 00:00:00");
+        assert!(cast_value("23:59:59", &SqlType::DateTime, &inferencer).is_err());
+
+        let zoned = cast_value(
+            "2024-03-01T10:00:00+02:00",
+            &SqlType::DateTimeTz,
+            &inferencer,
+        );
+        assert_eq!(
+            zoned.unwrap().temporal_text().unwrap(),
+            "2024-03-01 10:00:00+02:00"
+        );
+        let naive = cast_value("2024-03-01 10:00:00", &SqlType::DateTimeTz, &inferencer);
+        assert_eq!(
+            naive.unwrap().temporal_text().unwrap(),
+            "2024-03-01 10:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_caster_uses_column_date_format() {
+        let mut column = crate::types::ColumnStats::new("day".to_string());
+        column.sql_type = SqlType::Date;
+        column.date_format = Some("%d/%m/%Y".to_string());
+        let schema = TableSchema::new("t".to_string(), vec![column]);
+
+        let mut caster = RecordCaster::new(Some(schema), NullPolicy::new(vec![]));
+        caster
+            .set_headers(&StringRecord::from(vec!["day"]))
+            .unwrap();
+
+        let record = StringRecord::from(vec!["03/04/2024"]);
+        let values = caster.cast_record(&record).unwrap();
+        assert_eq!(values[0].temporal_text().unwrap(), "2024-04-03");
+    }
+}
@@ -0,0 +1,144 @@
+use crate::analyzer::nulls::NullPolicy;
+use crate::parser::cast::{RecordCaster, TypedValue};
+use crate::parser::output::{RecordWriter, WriteOutcome};
+use crate::types::TableSchema;
+use anyhow::Result;
+use csv::StringRecord;
+use serde_json::Value;
+use std::io::Write;
+
+/// Writes each record as a JSON object keyed by header name, one object per line.
+/// Keys keep the header order of the input.
+pub struct JsonLinesWriter<W: Write> {
+    output: W,
+    caster: RecordCaster,
+    keys: Vec<String>,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    pub fn new(output: W, schema: Option<TableSchema>, nulls: NullPolicy) -> Self {
+        JsonLinesWriter {
+            output,
+            caster: RecordCaster::new(schema, nulls),
+            keys: Vec::new(),
+        }
+    }
+
+    fn render_value(value: &TypedValue) -> Value {
+        match value {
+            TypedValue::Null => Value::Null,
+            TypedValue::Boolean(b) => Value::Bool(*b),
+            TypedValue::Integer(num) => Value::from(*num),
+            TypedValue::Float(num) => Value::from(*num),
+            // A JSON number would round through f64, so exact decimals stay strings
+            TypedValue::Decimal(text) => Value::from(*text),
+            TypedValue::Date(_)
+            | TypedValue::Time(_)
+            | TypedValue::DateTime(_)
+            | TypedValue::DateTimeTz(_) => Value::from(value.temporal_text().unwrap_or_default()),
+            // Already validated when cast, so this only fails on pathological nesting
+            TypedValue::Json(text) => {
+                serde_json::from_str(text).unwrap_or_else(|_| Value::from(*text))
+            }
+            TypedValue::Text(text) => Value::from(*text),
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for JsonLinesWriter<W> {
+    fn write_headers(&mut self, headers: &StringRecord) -> Result<()> {
+        self.keys = headers
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<_, _>>()?;
+        self.caster.set_headers(headers)
+    }
+
+    fn write_record(&mut self, record: &StringRecord) -> Result<WriteOutcome> {
+        let values = match self.caster.cast_record(record) {
+            Ok(values) => values,
+            Err(reason) => return Ok(WriteOutcome::Rejected(reason)),
+        };
+
+        let mut line = String::from("{");
+        for (i, (key, value)) in self.keys.iter().zip(values.iter()).enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            line.push_str(key);
+            line.push(':');
+            line.push_str(&Self::render_value(value).to_string());
+        }
+        line.push('}');
+
+        writeln!(self.output, "{}", line)?;
+        Ok(WriteOutcome::Written)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ColumnStats, SqlType};
+
+    #[test]
+    fn test_untyped_objects_keep_header_order() {
+        let mut output = Vec::new();
+        let mut writer =
+            JsonLinesWriter::new(&mut output, None, NullPolicy::new(vec!["NA".to_string()]));
+
+        writer
+            .write_headers(&StringRecord::from(vec!["z", "a"]))
+            .unwrap();
+        writer
+            .write_record(&StringRecord::from(vec!["1", "say \"hi\""]))
+            .unwrap();
+        writer
+            .write_record(&StringRecord::from(vec!["NA", "x"]))
+            .unwrap();
+        writer.finish().unwrap();
+
+        let jsonl = String::from_utf8(output).unwrap();
+        assert_eq!(
+            jsonl,
+            "{\"z\":\"1\",\"a\":\"say \\\"hi\\\"\"}\n{\"z\":null,\"a\":\"x\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_typed_values_from_schema() {
+        let mut columns = vec![
+            ColumnStats::new("id".to_string()),
+            ColumnStats::new("price".to_string()),
+            ColumnStats::new("active".to_string()),
+        ];
+        columns[0].sql_type = SqlType::Integer;
+        columns[1].sql_type = SqlType::DoublePrecision;
+        columns[2].sql_type = SqlType::Boolean;
+        let schema = TableSchema::new("t".to_string(), columns);
+
+        let mut output = Vec::new();
+        let mut writer = JsonLinesWriter::new(&mut output, Some(schema), NullPolicy::new(vec![]));
+
+        writer
+            .write_headers(&StringRecord::from(vec!["id", "price", "active"]))
+            .unwrap();
+        let written = writer
+            .write_record(&StringRecord::from(vec!["7", "2.50", "true"]))
+            .unwrap();
+        let rejected = writer
+            .write_record(&StringRecord::from(vec!["7", "cheap", "true"]))
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(written, WriteOutcome::Written);
+        assert!(matches!(rejected, WriteOutcome::Rejected(_)));
+        let jsonl = String::from_utf8(output).unwrap();
+        assert_eq!(jsonl, "{\"id\":7,\"price\":2.5,\"active\":true}\n");
+    }
+}
@@ -0,0 +1,314 @@
+use crate::parser::CsvDialect;
+use anyhow::{anyhow, Result};
+use csv::WriterBuilder;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader, Lines, Read};
+
+/// Written for JSON null and missing keys, so they stay NULL apart from empty
+/// strings; analyzers and writers read it as NULL whatever the null options say.
+/// String values that would read as it are escaped, see `unescape_input_null`.
+pub const JSON_NULL: &str = "\\N";
+
+/// A string value with one more backslash if it is backslashes followed by
+/// `JSON_NULL`, so the text `\N` is not read as NULL
+fn escape_json_null(text: &str) -> String {
+    match text.strip_suffix(JSON_NULL) {
+        Some(prefix) if prefix.bytes().all(|b| b == b'\\') => format!("\\{}", text),
+        _ => text.to_string(),
+    }
+}
+
+/// Flatten one JSON Lines record into (column, value) pairs.
+///
+/// Nested objects become `parent{separator}child` columns. Arrays and empty objects
+/// are kept as JSON text, and JSON null becomes `None`. A flattened name that
+/// repeats another, as `a_b` does `a.b` with separator `_`, is an error.
+pub fn flatten_record(line: &str, separator: &str) -> Result<Vec<(String, Option<String>)>> {
+    Ok(flatten_fields(line, separator)?
+        .into_iter()
+        .map(|field| (field.name, field.value))
+        .collect())
+}
+
+/// A flattened field with the keys leading to it, `["a", "b"]` for `a.b`
+struct Flattened {
+    name: String,
+    path: Vec<String>,
+    value: Option<String>,
+}
+
+fn flatten_fields(line: &str, separator: &str) -> Result<Vec<Flattened>> {
+    let value: Value = serde_json::from_str(line).map_err(|e| anyhow!("invalid JSON: {}", e))?;
+    let Value::Object(object) = value else {
+        return Err(anyhow!("expected a JSON object"));
+    };
+
+    let mut fields = Vec::new();
+    flatten_object(&object, &[], separator, &mut fields);
+
+    let mut names = HashSet::new();
+    if let Some(field) = fields.iter().find(|field| !names.insert(&field.name)) {
+        return Err(anyhow!(
+            "column '{}' appears twice after flattening; try another --flatten-separator",
+            field.name
+        ));
+    }
+    Ok(fields)
+}
+
+fn flatten_object(
+    object: &Map<String, Value>,
+    prefix: &[String],
+    separator: &str,
+    fields: &mut Vec<Flattened>,
+) {
+    for (key, value) in object {
+        let mut path = prefix.to_vec();
+        path.push(key.clone());
+
+        let value = match value {
+            Value::Object(nested) if !nested.is_empty() => {
+                flatten_object(nested, &path, separator, fields);
+                continue;
+            }
+            Value::Null => None,
+            Value::String(text) => Some(escape_json_null(text)),
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Number(num) => Some(num.to_string()),
+            Value::Array(_) | Value::Object(_) => Some(value.to_string()),
+        };
+        fields.push(Flattened {
+            name: path.join(separator),
+            path,
+            value,
+        });
+    }
+}
+
+/// Union of the flattened columns of every record, in order of first appearance.
+/// A name reached by different keys in different records is an error, as it
+/// is within one record.
+pub fn collect_columns<R: Read>(input: R, separator: &str) -> Result<Vec<String>> {
+    let mut columns = Vec::new();
+    let mut paths: HashMap<String, Vec<String>> = HashMap::new();
+
+    for (i, line) in BufReader::new(input).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields =
+            flatten_fields(&line, separator).map_err(|e| anyhow!("Line {}: {}", i + 1, e))?;
+        for field in fields {
+            match paths.get(&field.name) {
+                Some(path) if *path != field.path => {
+                    return Err(anyhow!(
+                        "Line {}: column '{}' comes from keys {:?} here but {:?} before; try another --flatten-separator",
+                        i + 1,
+                        field.name,
+                        field.path,
+                        path
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    columns.push(field.name.clone());
+                    paths.insert(field.name, field.path);
+                }
+            }
+        }
+    }
+
+    Ok(columns)
+}
+
+/// Streams JSON Lines records as delimited text with a header row of `columns`,
+/// so they flow through the same reader and analysis as CSV input. Only the
+/// current record is held in memory.
+pub struct JsonlCsvReader<R: Read> {
+    lines: Lines<BufReader<R>>,
+    column_index: HashMap<String, usize>,
+    column_count: usize,
+    separator: String,
+    writer_builder: WriterBuilder,
+    buffer: Vec<u8>,
+    position: usize,
+    line_number: usize,
+}
+
+impl<R: Read> JsonlCsvReader<R> {
+    pub fn new(
+        input: R,
+        columns: Vec<String>,
+        separator: &str,
+        dialect: &CsvDialect,
+    ) -> io::Result<Self> {
+        let mut reader = JsonlCsvReader {
+            lines: BufReader::new(input).lines(),
+            column_index: columns
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), i))
+                .collect(),
+            column_count: columns.len(),
+            separator: separator.to_string(),
+            writer_builder: dialect.writer_builder(),
+            buffer: Vec::new(),
+            position: 0,
+            line_number: 0,
+        };
+
+        reader.write_row(columns.iter().map(String::as_str))?;
+        Ok(reader)
+    }
+
+    fn write_row<'a>(&mut self, fields: impl Iterator<Item = &'a str>) -> io::Result<()> {
+        self.buffer.clear();
+        self.position = 0;
+
+        let mut writer = self
+            .writer_builder
+            .from_writer(std::mem::take(&mut self.buffer));
+        writer.write_record(fields)?;
+        self.buffer = writer
+            .into_inner()
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Fill the buffer with the next record, returning false at end of input
+    fn next_record(&mut self) -> io::Result<bool> {
+        loop {
+            let Some(line) = self.lines.next() else {
+                return Ok(false);
+            };
+            let line = line?;
+            self.line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields = flatten_record(&line, &self.separator).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Line {}: {}", self.line_number, e),
+                )
+            })?;
+
+            // Keys missing from this record are NULL, like JSON null
+            let mut row = vec![JSON_NULL.to_string(); self.column_count];
+            for (name, value) in fields {
+                if let (Some(&i), Some(value)) = (self.column_index.get(&name), value) {
+                    row[i] = value;
+                }
+            }
+
+            self.write_row(row.iter().map(String::as_str))?;
+            return Ok(true);
+        }
+    }
+}
+
+impl<R: Read> Read for JsonlCsvReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.buffer.len() && !self.next_record()? {
+            return Ok(0);
+        }
+
+        let available = &self.buffer[self.position..];
+        let to_copy = available.len().min(buf.len());
+        buf[..to_copy].copy_from_slice(&available[..to_copy]);
+        self.position += to_copy;
+        Ok(to_copy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::QuoteStyle;
+
+    const INPUT: &str = r#"{"id": 1, "user": {"name": "Al", "address": {"city": "Oslo"}}, "tags": ["a"]}
+
+{"id": 2, "user": {"name": null}, "extra": {}, "note": "x, y", "tags": ""}
+"#;
+
+    #[test]
+    fn test_flatten_record() {
+        let fields = flatten_record(r#"{"a": {"b": 1, "c": [1, 2]}, "d": null}"#, ".").unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                ("a.b".to_string(), Some("1".to_string())),
+                ("a.c".to_string(), Some("[1,2]".to_string())),
+                ("d".to_string(), None),
+            ]
+        );
+        assert!(flatten_record(r#"{"a_b": 1, "a": {"b": 2}}"#, "_").is_err());
+        assert_eq!(
+            flatten_record(r#"{"a": "\\N", "b": "\\\\N", "c": "x\\N"}"#, "_").unwrap(),
+            vec![
+                ("a".to_string(), Some("\\\\N".to_string())),
+                ("b".to_string(), Some("\\\\\\N".to_string())),
+                ("c".to_string(), Some("x\\N".to_string())),
+            ]
+        );
+        assert!(flatten_record("[1, 2]", "_").is_err());
+        assert!(flatten_record("{\"a\": ", "_").is_err());
+    }
+
+    #[test]
+    fn test_names_colliding_across_records() {
+        let input = "{\"a\": {\"b\": 1}}\n{\"a_b\": \"zz\"}\n";
+        let error = collect_columns(input.as_bytes(), "_").unwrap_err();
+        assert!(
+            error.to_string().starts_with("Line 2: column 'a_b'"),
+            "{}",
+            error
+        );
+        assert_eq!(
+            collect_columns(input.as_bytes(), ".").unwrap(),
+            vec!["a.b", "a_b"]
+        );
+
+        let repeated = "{\"a\": {\"b\": 1}}\n{\"a\": {\"b\": 2}}\n";
+        assert_eq!(
+            collect_columns(repeated.as_bytes(), "_").unwrap(),
+            vec!["a_b"]
+        );
+    }
+
+    #[test]
+    fn test_jsonl_as_csv() {
+        let columns = collect_columns(INPUT.as_bytes(), "_").unwrap();
+        assert_eq!(
+            columns,
+            vec![
+                "id",
+                "user_name",
+                "user_address_city",
+                "tags",
+                "extra",
+                "note"
+            ]
+        );
+
+        let dialect = CsvDialect {
+            delimiter: ',',
+            quote: QuoteStyle::Double,
+            escquote: None,
+        };
+        let mut reader = JsonlCsvReader::new(INPUT.as_bytes(), columns, "_", &dialect).unwrap();
+        let mut csv = String::new();
+        reader.read_to_string(&mut csv).unwrap();
+
+        assert_eq!(
+            csv,
+            "id,user_name,user_address_city,tags,extra,note\n\
+             1,Al,Oslo,\"[\"\"a\"\"]\",\\N,\\N\n\
+             2,\\N,\\N,,{},\"x, y\"\n"
+        );
+    }
+}
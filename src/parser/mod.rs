@@ -1,41 +1,217 @@
+pub mod cast;
+pub mod jsonl;
+pub mod jsonl_reader;
+pub mod output;
+pub mod parquet;
+pub mod sql_insert;
 pub mod streaming;
+pub mod workbook;
 
-use crate::cli::ParseArgs;
-use anyhow::{Context, Result};
+use crate::analyzer::infer_schema;
+use crate::analyzer::patterns::CellHints;
+use crate::cli::{InputFormat, OutputFormat, ParseArgs, QuoteStyle};
+use anyhow::{bail, Context, Result};
+use csv::{QuoteStyle as CsvQuoteStyle, WriterBuilder};
 use encoding_rs::Encoding;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{BufReader, BufWriter, Cursor, Read, Write};
+use std::path::Path;
 pub use streaming::ParsedCsvReader;
 
 pub fn parse_command(args: ParseArgs) -> Result<()> {
-    let input: Box<dyn Read> = match &args.input {
-        Some(path) => Box::new(std::fs::File::open(path)?),
-        None => Box::new(std::io::stdin()),
-    };
-
-    let output: Box<dyn Write> = match &args.output {
+    let output: Box<dyn Write + Send> = match &args.output {
         Some(path) => Box::new(std::fs::File::create(path)?),
         None => Box::new(std::io::stdout()),
     };
 
-    // Handle encoding
-    let encoding = Encoding::for_label(args.encoding.as_bytes())
-        .with_context(|| format!("Unsupported encoding: {}", args.encoding))?;
+    // Parquet needs column types; without --schema they come from a first pass over the input
+    let mut spool = None;
+    let schema = match output::load_schema(&args)? {
+        Some(schema) => Some(schema),
+        None if args.format == OutputFormat::Parquet => {
+            if args.input.is_none() {
+                // stdin can only be read once, so keep a copy for the second pass
+                let mut file = tempfile::NamedTempFile::new()?;
+                std::io::copy(&mut std::io::stdin(), &mut file)?;
+                spool = Some(file);
+            }
 
-    let reader = if encoding == encoding_rs::UTF_8 {
-        BufReader::with_capacity(8192, input)
-    } else {
-        // For non-UTF8 encodings, we need to decode first
-        let decoded_reader = EncodingReader::new(input, encoding);
-        BufReader::with_capacity(8192, Box::new(decoded_reader) as Box<dyn Read>)
+            let (reader, hints) = open_input(&args, spool.as_ref().map(|f| f.path()))?;
+            let schema = infer_schema(reader, &args, hints)?;
+            if args.verbose {
+                eprintln!(
+                    "Inferred schema for {} columns in first pass",
+                    schema.columns.len()
+                );
+            }
+            Some(schema)
+        }
+        None => None,
     };
 
+    let (reader, _) = open_input(&args, spool.as_ref().map(|f| f.path()))?;
     let writer = BufWriter::with_capacity(8192, output);
 
-    streaming::process_csv(reader, writer, &args)?;
+    streaming::process_csv_with_schema(reader, writer, &args, schema)?;
 
     Ok(())
 }
 
+/// Open the parse input, reading `spool` in place of stdin when given, with
+/// the workbook cell hints of its columns
+fn open_input(
+    args: &ParseArgs,
+    spool: Option<&Path>,
+) -> Result<(BufReader<Box<dyn Read>>, CellHints)> {
+    let source = InputSource {
+        path: spool.or(args.input.as_deref()),
+        ..InputSource::from_parse_args(args)
+    };
+
+    let (reader, hints) = source.open_with_hints()?;
+    Ok((BufReader::with_capacity(8192, reader), hints))
+}
+
+/// Delimited-text settings shared by the CSV reader and the converters that feed it
+pub struct CsvDialect {
+    pub delimiter: char,
+    pub quote: QuoteStyle,
+    pub escquote: Option<char>,
+}
+
+impl CsvDialect {
+    /// A writer producing text that a reader configured with the same settings reads back
+    pub fn writer_builder(&self) -> WriterBuilder {
+        let mut writer_builder = WriterBuilder::new();
+        writer_builder
+            .delimiter(self.delimiter as u8)
+            .flexible(true);
+
+        match self.quote.as_byte() {
+            Some(quote_byte) => {
+                writer_builder.quote(quote_byte);
+            }
+            None => {
+                writer_builder.quote_style(CsvQuoteStyle::Never);
+            }
+        }
+        if let Some(esc) = self.escquote {
+            writer_builder.escape(esc as u8).double_quote(false);
+        }
+
+        writer_builder
+    }
+}
+
+/// Command input: a file or stdin, plus what is needed to present it as delimited text
+pub struct InputSource<'a> {
+    pub path: Option<&'a Path>,
+    pub format: InputFormat,
+    pub encoding: &'a str,
+    pub sheet: Option<&'a str>,
+    pub range: Option<&'a str>,
+    pub flatten_separator: &'a str,
+    pub dialect: CsvDialect,
+}
+
+impl<'a> InputSource<'a> {
+    pub fn from_parse_args(args: &'a ParseArgs) -> Self {
+        InputSource {
+            path: args.input.as_deref(),
+            format: InputFormat::resolve(args.input_format, args.input.as_deref()),
+            encoding: &args.encoding,
+            sheet: args.sheet.as_deref(),
+            range: args.range.as_deref(),
+            flatten_separator: &args.flatten_separator,
+            dialect: CsvDialect {
+                delimiter: args.delimiter,
+                quote: args.quote,
+                escquote: args.escquote,
+            },
+        }
+    }
+
+    /// The token the delimited text uses for the input format's own NULL
+    pub fn null_token(&self) -> Option<&'static str> {
+        (self.format == InputFormat::Jsonl).then_some(jsonl_reader::JSON_NULL)
+    }
+
+    /// Open the input as delimited text. Non-UTF-8 input is decoded, workbooks are
+    /// converted from the selected sheet and JSON Lines records are flattened.
+    pub fn open(&self) -> Result<Box<dyn Read>> {
+        Ok(self.open_with_hints()?.0)
+    }
+
+    /// Like `open`, also giving each column's `CellHint` for workbook input
+    pub fn open_with_hints(&self) -> Result<(Box<dyn Read>, CellHints)> {
+        if self.format != InputFormat::Workbook && (self.sheet.is_some() || self.range.is_some()) {
+            bail!("--sheet and --range require .xlsx, .xls or .ods input");
+        }
+
+        match self.format {
+            InputFormat::Csv => Ok((self.open_text()?, Vec::new())),
+            InputFormat::Workbook => {
+                let path = self
+                    .path
+                    .context("Workbook input must be read from a file, not stdin")?;
+                let (csv, hints) =
+                    workbook::read_workbook(path, self.sheet, self.range, &self.dialect)?;
+                Ok((Box::new(Cursor::new(csv)), hints))
+            }
+            InputFormat::Jsonl => Ok((self.open_jsonl()?, Vec::new())),
+            InputFormat::Parquet | InputFormat::Arrow => {
+                bail!("Parquet and Arrow IPC input is only supported by describe")
+            }
+        }
+    }
+
+    fn open_text(&self) -> Result<Box<dyn Read>> {
+        let input: Box<dyn Read> = match self.path {
+            Some(path) => Box::new(std::fs::File::open(path)?),
+            None => Box::new(std::io::stdin()),
+        };
+        self.decode(input)
+    }
+
+    fn decode(&self, input: Box<dyn Read>) -> Result<Box<dyn Read>> {
+        // Handle encoding
+        let encoding = Encoding::for_label(self.encoding.as_bytes())
+            .with_context(|| format!("Unsupported encoding: {}", self.encoding))?;
+
+        if encoding == encoding_rs::UTF_8 {
+            Ok(input)
+        } else {
+            // For non-UTF8 encodings, we need to decode first
+            Ok(Box::new(EncodingReader::new(input, encoding)))
+        }
+    }
+
+    /// JSON Lines are read twice: once to union the keys into columns, then to emit rows
+    fn open_jsonl(&self) -> Result<Box<dyn Read>> {
+        let (first, second): (Box<dyn Read>, Box<dyn Read>) = match self.path {
+            Some(path) => (
+                Box::new(std::fs::File::open(path)?),
+                Box::new(std::fs::File::open(path)?),
+            ),
+            None => {
+                // stdin can only be read once, so keep a copy for the second pass.
+                // Reopened handles have their own cursors and outlive the spool's name.
+                let mut spool = tempfile::NamedTempFile::new()?;
+                std::io::copy(&mut std::io::stdin(), &mut spool)?;
+                (Box::new(spool.reopen()?), Box::new(spool.reopen()?))
+            }
+        };
+
+        let columns = jsonl_reader::collect_columns(self.decode(first)?, self.flatten_separator)?;
+        let reader = jsonl_reader::JsonlCsvReader::new(
+            self.decode(second)?,
+            columns,
+            self.flatten_separator,
+            &self.dialect,
+        )?;
+        Ok(Box::new(reader))
+    }
+}
+
 // Custom reader that handles encoding conversion
 pub struct EncodingReader {
     inner: Box<dyn Read>,
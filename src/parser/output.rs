@@ -0,0 +1,228 @@
+use crate::analyzer::nulls::{unescape_input_null, NullPolicy};
+use crate::analyzer::overrides::Overrides;
+use crate::cli::{OutputFormat, ParseArgs};
+use crate::database::resolve_dialect;
+use crate::parser::jsonl::JsonLinesWriter;
+use crate::parser::parquet::ParquetRecordWriter;
+use crate::parser::sql_insert::SqlInsertWriter;
+use crate::parser::InputSource;
+use crate::types::TableSchema;
+use crate::utils::default_table_name;
+use anyhow::{Context, Result};
+use csv::{StringRecord, WriterBuilder};
+use std::io::Write;
+
+/// Result of handing a record to a `RecordWriter`
+#[derive(Debug, PartialEq)]
+pub enum WriteOutcome {
+    Written,
+    /// The record was not written; the reason is reported as a bad row
+    Rejected(String),
+}
+
+/// Destination for the cleaned records produced by `process_csv`
+pub trait RecordWriter {
+    fn write_headers(&mut self, headers: &StringRecord) -> Result<()>;
+    fn write_record(&mut self, record: &StringRecord) -> Result<WriteOutcome>;
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// The `--fnull` values and the input format's own NULL token, which the
+/// CSV and TSV writers replace with `--tnull`
+struct TextNulls {
+    fnull: Vec<String>,
+    input_null: Option<&'static str>,
+}
+
+impl TextNulls {
+    fn new(args: &ParseArgs) -> Self {
+        TextNulls {
+            fnull: args.fnull.clone(),
+            input_null: InputSource::from_parse_args(args).null_token(),
+        }
+    }
+
+    /// The field as written, or `None` if it is NULL
+    fn read<'a>(&self, field: &'a str) -> Option<&'a str> {
+        let field = match self.input_null {
+            Some(token) if token == field => return None,
+            Some(token) => unescape_input_null(token, field),
+            None => field,
+        };
+        (!self.fnull.iter().any(|null| null == field)).then_some(field)
+    }
+}
+
+/// RFC 4180 output, transforming `--fnull` values into `--tnull`
+pub struct CsvRecordWriter<W: Write> {
+    writer: csv::Writer<W>,
+    nulls: TextNulls,
+    tnull: String,
+}
+
+impl<W: Write> CsvRecordWriter<W> {
+    pub fn new(output: W, args: &ParseArgs) -> Self {
+        let mut writer_builder = WriterBuilder::new();
+        writer_builder
+            .delimiter(args.delimiter as u8)
+            .double_quote(true); // RFC 4180 compliant double quote escaping
+
+        if let Some(quote_byte) = args.quote.as_byte() {
+            writer_builder.quote(quote_byte);
+        }
+
+        CsvRecordWriter {
+            writer: writer_builder.from_writer(output),
+            nulls: TextNulls::new(args),
+            tnull: args.tnull.clone(),
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for CsvRecordWriter<W> {
+    fn write_headers(&mut self, headers: &StringRecord) -> Result<()> {
+        self.writer.write_record(headers)?;
+        Ok(())
+    }
+
+    fn write_record(&mut self, record: &StringRecord) -> Result<WriteOutcome> {
+        let transformed = record
+            .iter()
+            .map(|field| self.nulls.read(field).unwrap_or(&self.tnull));
+        self.writer.write_record(transformed)?;
+        Ok(WriteOutcome::Written)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Tab-separated output without quoting. Backslash, tab, newline and carriage return
+/// inside fields are written as `\\`, `\t`, `\n` and `\r`; `--tnull` is written as
+/// given, so `\N` stays a NULL marker.
+pub struct TsvRecordWriter<W: Write> {
+    output: W,
+    nulls: TextNulls,
+    tnull: String,
+}
+
+impl<W: Write> TsvRecordWriter<W> {
+    pub fn new(output: W, args: &ParseArgs) -> Self {
+        TsvRecordWriter {
+            output,
+            nulls: TextNulls::new(args),
+            tnull: args.tnull.clone(),
+        }
+    }
+}
+
+/// Append a field, escaping the characters that would break the line
+fn push_tsv_field(line: &mut String, field: &str) {
+    for c in field.chars() {
+        match c {
+            '\\' => line.push_str("\\\\"),
+            '\t' => line.push_str("\\t"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            _ => line.push(c),
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for TsvRecordWriter<W> {
+    fn write_headers(&mut self, headers: &StringRecord) -> Result<()> {
+        let mut line = String::new();
+        for (i, header) in headers.iter().enumerate() {
+            if i > 0 {
+                line.push('\t');
+            }
+            push_tsv_field(&mut line, header);
+        }
+        writeln!(self.output, "{}", line)?;
+        Ok(())
+    }
+
+    fn write_record(&mut self, record: &StringRecord) -> Result<WriteOutcome> {
+        let mut line = String::new();
+        for (i, field) in record.iter().enumerate() {
+            if i > 0 {
+                line.push('\t');
+            }
+            match self.nulls.read(field) {
+                Some(field) => push_tsv_field(&mut line, field),
+                None => line.push_str(&self.tnull),
+            }
+        }
+        writeln!(self.output, "{}", line)?;
+        Ok(WriteOutcome::Written)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+/// Load the `--schema` file, if one was given
+pub fn load_schema(args: &ParseArgs) -> Result<Option<TableSchema>> {
+    match &args.schema {
+        Some(path) => Ok(Some(TableSchema::from_file(path).with_context(|| {
+            format!("Failed to load schema file: {}", path.display())
+        })?)),
+        None => Ok(None),
+    }
+}
+
+/// The NULL rules of `describe`, so typed output reads NULL where the schema did
+fn null_policy(args: &ParseArgs) -> NullPolicy {
+    NullPolicy::new(args.fnull.clone())
+        .with_default_nulls(!args.no_default_nulls)
+        .with_blank_values(args.blank_values)
+        .with_columns(Overrides::default().with_null_values(&args.fnull_column))
+        .with_input_null(InputSource::from_parse_args(args).null_token())
+}
+
+/// Build the writer for the requested `--format`
+pub fn create_record_writer<'a, W: Write + Send + 'a>(
+    output: W,
+    args: &ParseArgs,
+    schema: Option<TableSchema>,
+) -> Result<Box<dyn RecordWriter + 'a>> {
+    match args.format {
+        OutputFormat::Csv => Ok(Box::new(CsvRecordWriter::new(output, args))),
+        OutputFormat::Tsv => Ok(Box::new(TsvRecordWriter::new(output, args))),
+        OutputFormat::Jsonl => Ok(Box::new(JsonLinesWriter::new(
+            output,
+            schema,
+            null_policy(args),
+        ))),
+        OutputFormat::SqlInsert => {
+            let dialect = resolve_dialect(&args.database, args.database_config.as_deref())?;
+            let table = args
+                .table
+                .clone()
+                .or_else(|| schema.as_ref().map(|s| s.table_name.clone()))
+                .unwrap_or_else(|| default_table_name(args.input.as_deref()));
+
+            Ok(Box::new(SqlInsertWriter::new(
+                output,
+                dialect,
+                table,
+                schema,
+                null_policy(args),
+                args.batch_size as usize,
+            )))
+        }
+        OutputFormat::Parquet => {
+            let schema = schema.context("Parquet output requires a schema")?;
+            Ok(Box::new(ParquetRecordWriter::new(
+                output,
+                schema,
+                null_policy(args),
+                args.row_group_size as usize,
+            )))
+        }
+    }
+}
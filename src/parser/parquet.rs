@@ -0,0 +1,499 @@
+use crate::analyzer::nulls::NullPolicy;
+use crate::parser::cast::{RecordCaster, TypedValue};
+use crate::parser::output::{RecordWriter, WriteOutcome};
+use crate::types::{SqlType, TableSchema};
+use anyhow::Result;
+use arrow_array::builder::{
+    BooleanBuilder, Date32Builder, Decimal128Builder, Float64Builder, Int16Builder, Int32Builder,
+    Int64Builder, Int8Builder, StringBuilder, Time64MicrosecondBuilder,
+    TimestampMicrosecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{NaiveDate, Timelike};
+use csv::StringRecord;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::sync::Arc;
+
+const MAX_DECIMAL128_PRECISION: u16 = 38;
+
+/// Arrow type used to store a column of the given `SqlType`
+pub fn arrow_data_type(sql_type: &SqlType) -> DataType {
+    match sql_type {
+        SqlType::Boolean => DataType::Boolean,
+        SqlType::TinyInt => DataType::Int8,
+        SqlType::SmallInt => DataType::Int16,
+        SqlType::Integer => DataType::Int32,
+        SqlType::BigInt => DataType::Int64,
+        SqlType::Decimal { precision, scale } if *precision <= MAX_DECIMAL128_PRECISION => {
+            DataType::Decimal128(*precision as u8, *scale as i8)
+        }
+        // Wider decimals are kept exact as text
+        SqlType::Decimal { .. } => DataType::Utf8,
+        SqlType::DoublePrecision => DataType::Float64,
+        SqlType::Date => DataType::Date32,
+        SqlType::Time => DataType::Time64(TimeUnit::Microsecond),
+        SqlType::DateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+        // Stored as UTC instants; the original offsets are not kept
+        SqlType::DateTimeTz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        SqlType::Json | SqlType::Varchar(_) => DataType::Utf8,
+    }
+}
+
+/// One Arrow array builder per column, matching `arrow_data_type`
+enum ColumnBuilder {
+    Boolean(BooleanBuilder),
+    Int8(Int8Builder),
+    Int16(Int16Builder),
+    Int32(Int32Builder),
+    Int64(Int64Builder),
+    Decimal128(Decimal128Builder, u16),
+    Float64(Float64Builder),
+    Date32(Date32Builder),
+    Time64(Time64MicrosecondBuilder),
+    Timestamp(TimestampMicrosecondBuilder),
+    Utf8(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(sql_type: &SqlType) -> Self {
+        match sql_type {
+            SqlType::Boolean => ColumnBuilder::Boolean(BooleanBuilder::new()),
+            SqlType::TinyInt => ColumnBuilder::Int8(Int8Builder::new()),
+            SqlType::SmallInt => ColumnBuilder::Int16(Int16Builder::new()),
+            SqlType::Integer => ColumnBuilder::Int32(Int32Builder::new()),
+            SqlType::BigInt => ColumnBuilder::Int64(Int64Builder::new()),
+            SqlType::Decimal { scale, .. } => match arrow_data_type(sql_type) {
+                DataType::Utf8 => ColumnBuilder::Utf8(StringBuilder::new()),
+                data_type => ColumnBuilder::Decimal128(
+                    Decimal128Builder::new().with_data_type(data_type),
+                    *scale,
+                ),
+            },
+            SqlType::DoublePrecision => ColumnBuilder::Float64(Float64Builder::new()),
+            SqlType::Date => ColumnBuilder::Date32(Date32Builder::new()),
+            SqlType::Time => ColumnBuilder::Time64(Time64MicrosecondBuilder::new()),
+            SqlType::DateTime => ColumnBuilder::Timestamp(TimestampMicrosecondBuilder::new()),
+            SqlType::DateTimeTz => {
+                ColumnBuilder::Timestamp(TimestampMicrosecondBuilder::new().with_timezone("UTC"))
+            }
+            SqlType::Json | SqlType::Varchar(_) => ColumnBuilder::Utf8(StringBuilder::new()),
+        }
+    }
+
+    /// The Arrow value of one already cast by `RecordCaster`, or why this
+    /// column cannot store it
+    fn cell<'a>(&self, value: &TypedValue<'a>) -> Result<Cell<'a>, String> {
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid epoch");
+
+        Ok(match (self, value) {
+            (_, TypedValue::Null) => Cell::Null,
+            (ColumnBuilder::Boolean(_), TypedValue::Boolean(v)) => Cell::Boolean(*v),
+            (
+                ColumnBuilder::Int8(_)
+                | ColumnBuilder::Int16(_)
+                | ColumnBuilder::Int32(_)
+                | ColumnBuilder::Int64(_),
+                TypedValue::Integer(v),
+            ) => Cell::Integer(*v),
+            (ColumnBuilder::Decimal128(_, scale), TypedValue::Decimal(v)) => {
+                match scaled_decimal(v, *scale) {
+                    Some(unscaled) => Cell::Decimal(unscaled),
+                    None => return Err(format!("'{}' does not fit a 128-bit decimal", v)),
+                }
+            }
+            (ColumnBuilder::Float64(_), TypedValue::Float(v)) => Cell::Float(*v),
+            (ColumnBuilder::Date32(_), TypedValue::Date(d)) => {
+                Cell::Days((*d - epoch).num_days() as i32)
+            }
+            (ColumnBuilder::Time64(_), TypedValue::Time(t)) => Cell::Micros(
+                t.num_seconds_from_midnight() as i64 * 1_000_000 + t.nanosecond() as i64 / 1_000,
+            ),
+            (ColumnBuilder::Timestamp(_), TypedValue::DateTime(dt)) => {
+                Cell::Micros(dt.and_utc().timestamp_micros())
+            }
+            (ColumnBuilder::Timestamp(_), TypedValue::DateTimeTz(dt)) => {
+                Cell::Micros(dt.timestamp_micros())
+            }
+            (
+                ColumnBuilder::Utf8(_),
+                TypedValue::Decimal(v) | TypedValue::Json(v) | TypedValue::Text(v),
+            ) => Cell::Text(v),
+            (_, value) => return Err(format!("{:?} does not match the Parquet column", value)),
+        })
+    }
+
+    /// Append a cell returned by `cell` for this column
+    fn append(&mut self, cell: Cell) {
+        match (self, cell) {
+            (ColumnBuilder::Boolean(b), Cell::Boolean(v)) => b.append_value(v),
+            (ColumnBuilder::Int8(b), Cell::Integer(v)) => b.append_value(v as i8),
+            (ColumnBuilder::Int16(b), Cell::Integer(v)) => b.append_value(v as i16),
+            (ColumnBuilder::Int32(b), Cell::Integer(v)) => b.append_value(v as i32),
+            (ColumnBuilder::Int64(b), Cell::Integer(v)) => b.append_value(v),
+            (ColumnBuilder::Decimal128(b, _), Cell::Decimal(v)) => b.append_value(v),
+            (ColumnBuilder::Float64(b), Cell::Float(v)) => b.append_value(v),
+            (ColumnBuilder::Date32(b), Cell::Days(v)) => b.append_value(v),
+            (ColumnBuilder::Time64(b), Cell::Micros(v)) => b.append_value(v),
+            (ColumnBuilder::Timestamp(b), Cell::Micros(v)) => b.append_value(v),
+            (ColumnBuilder::Utf8(b), Cell::Text(v)) => b.append_value(v),
+            // `cell` gives every other column only NULL
+            (builder, _) => builder.append_null(),
+        }
+    }
+
+    fn append_null(&mut self) {
+        match self {
+            ColumnBuilder::Boolean(b) => b.append_null(),
+            ColumnBuilder::Int8(b) => b.append_null(),
+            ColumnBuilder::Int16(b) => b.append_null(),
+            ColumnBuilder::Int32(b) => b.append_null(),
+            ColumnBuilder::Int64(b) => b.append_null(),
+            ColumnBuilder::Decimal128(b, _) => b.append_null(),
+            ColumnBuilder::Float64(b) => b.append_null(),
+            ColumnBuilder::Date32(b) => b.append_null(),
+            ColumnBuilder::Time64(b) => b.append_null(),
+            ColumnBuilder::Timestamp(b) => b.append_null(),
+            ColumnBuilder::Utf8(b) => b.append_null(),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Boolean(b) => Arc::new(b.finish()),
+            ColumnBuilder::Int8(b) => Arc::new(b.finish()),
+            ColumnBuilder::Int16(b) => Arc::new(b.finish()),
+            ColumnBuilder::Int32(b) => Arc::new(b.finish()),
+            ColumnBuilder::Int64(b) => Arc::new(b.finish()),
+            ColumnBuilder::Decimal128(b, _) => Arc::new(b.finish()),
+            ColumnBuilder::Float64(b) => Arc::new(b.finish()),
+            ColumnBuilder::Date32(b) => Arc::new(b.finish()),
+            ColumnBuilder::Time64(b) => Arc::new(b.finish()),
+            ColumnBuilder::Timestamp(b) => Arc::new(b.finish()),
+            ColumnBuilder::Utf8(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+/// A value in the form its Arrow builder stores it
+enum Cell<'a> {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    /// Unscaled, see `scaled_decimal`
+    Decimal(i128),
+    Float(f64),
+    /// Days since the Unix epoch
+    Days(i32),
+    /// Microseconds since midnight or the Unix epoch
+    Micros(i64),
+    Text(&'a str),
+}
+
+/// Unscaled integer of a validated decimal literal, e.g. `-1.5` at scale 2 is -150
+fn scaled_decimal(text: &str, scale: u16) -> Option<i128> {
+    let (int_part, frac_part) = text.split_once('.').unwrap_or((text, ""));
+    let digits = format!("{}{:0<width$}", int_part, frac_part, width = scale as usize);
+    match digits.as_str() {
+        "" | "-" => Some(0),
+        digits => digits.parse().ok(),
+    }
+}
+
+/// Writes records to a Parquet file typed by a `TableSchema`.
+/// At most `row_group_size` rows are buffered before a row group is flushed.
+pub struct ParquetRecordWriter<W: Write + Send> {
+    output: Option<W>,
+    writer: Option<ArrowWriter<W>>,
+    table_schema: TableSchema,
+    arrow_schema: Option<SchemaRef>,
+    caster: RecordCaster,
+    builders: Vec<ColumnBuilder>,
+    nullable: Vec<bool>,
+    row_group_size: usize,
+    buffered_rows: usize,
+}
+
+impl<W: Write + Send> ParquetRecordWriter<W> {
+    pub fn new(
+        output: W,
+        table_schema: TableSchema,
+        nulls: NullPolicy,
+        row_group_size: usize,
+    ) -> Self {
+        ParquetRecordWriter {
+            output: Some(output),
+            writer: None,
+            caster: RecordCaster::new(Some(table_schema.clone()), nulls),
+            table_schema,
+            arrow_schema: None,
+            builders: Vec::new(),
+            nullable: Vec::new(),
+            row_group_size: row_group_size.max(1),
+            buffered_rows: 0,
+        }
+    }
+
+    fn flush_row_group(&mut self) -> Result<()> {
+        if self.buffered_rows == 0 {
+            return Ok(());
+        }
+
+        let (Some(writer), Some(schema)) = (self.writer.as_mut(), self.arrow_schema.as_ref())
+        else {
+            return Ok(());
+        };
+
+        let columns: Vec<ArrayRef> = self.builders.iter_mut().map(|b| b.finish()).collect();
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+        writer.write(&batch)?;
+        writer.flush()?;
+        self.buffered_rows = 0;
+        Ok(())
+    }
+}
+
+impl<W: Write + Send> RecordWriter for ParquetRecordWriter<W> {
+    fn write_headers(&mut self, headers: &StringRecord) -> Result<()> {
+        // Input without a header row takes its columns from the schema
+        let headers = if headers.is_empty() {
+            self.table_schema
+                .columns
+                .iter()
+                .map(|column| column.name.as_str())
+                .collect()
+        } else {
+            headers.clone()
+        };
+        self.caster.set_headers(&headers)?;
+
+        let mut fields = Vec::with_capacity(headers.len());
+        for header in headers.iter() {
+            let column = self
+                .table_schema
+                .column(header)
+                .ok_or_else(|| anyhow::anyhow!("Column '{}' not found in schema", header))?;
+            fields.push(Field::new(
+                header,
+                arrow_data_type(&column.sql_type),
+                column.is_nullable(),
+            ));
+            self.builders.push(ColumnBuilder::new(&column.sql_type));
+            self.nullable.push(column.is_nullable());
+        }
+
+        let schema: SchemaRef = Arc::new(Schema::new(fields));
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(self.row_group_size)
+            .build();
+        let output = self
+            .output
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Parquet headers already written"))?;
+
+        self.writer = Some(ArrowWriter::try_new(output, schema.clone(), Some(props))?);
+        self.arrow_schema = Some(schema);
+        Ok(())
+    }
+
+    fn write_record(&mut self, record: &StringRecord) -> Result<WriteOutcome> {
+        let values = match self.caster.cast_record(record) {
+            Ok(values) => values,
+            Err(reason) => return Ok(WriteOutcome::Rejected(reason)),
+        };
+
+        if let Some(i) = values
+            .iter()
+            .zip(&self.nullable)
+            .position(|(value, nullable)| *value == TypedValue::Null && !nullable)
+        {
+            return Ok(WriteOutcome::Rejected(format!(
+                "Column {}: NULL in non-nullable column",
+                i + 1
+            )));
+        }
+
+        // Every cell is checked before any is appended, so a rejected row
+        // leaves no partial values behind
+        let cells = match self
+            .builders
+            .iter()
+            .zip(&values)
+            .enumerate()
+            .map(|(i, (builder, value))| {
+                builder
+                    .cell(value)
+                    .map_err(|reason| format!("Column {}: {}", i + 1, reason))
+            })
+            .collect::<Result<Vec<Cell>, String>>()
+        {
+            Ok(cells) => cells,
+            Err(reason) => return Ok(WriteOutcome::Rejected(reason)),
+        };
+        for (builder, cell) in self.builders.iter_mut().zip(cells) {
+            builder.append(cell);
+        }
+        self.buffered_rows += 1;
+
+        if self.buffered_rows >= self.row_group_size {
+            self.flush_row_group()?;
+        }
+
+        Ok(WriteOutcome::Written)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        // Even empty input gets a schema and footer, making a valid file of no rows
+        if self.output.is_some() {
+            self.write_headers(&StringRecord::new())?;
+        }
+        self.flush_row_group()?;
+        if let Some(writer) = self.writer.take() {
+            writer.close()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ColumnStats;
+    use arrow_array::{Array, Date32Array, Int16Array, StringArray};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use tempfile::NamedTempFile;
+
+    fn test_schema() -> TableSchema {
+        let mut columns = vec![
+            ColumnStats::new("id".to_string()),
+            ColumnStats::new("name".to_string()),
+            ColumnStats::new("joined".to_string()),
+        ];
+        columns[0].sql_type = SqlType::SmallInt;
+        columns[1].sql_type = SqlType::Varchar(Some(10));
+        columns[1].null_count = 1;
+        columns[2].sql_type = SqlType::Date;
+        TableSchema::new("t".to_string(), columns)
+    }
+
+    #[test]
+    fn test_writes_typed_row_groups() {
+        let file = NamedTempFile::new().unwrap();
+        let mut writer = ParquetRecordWriter::new(
+            file.reopen().unwrap(),
+            test_schema(),
+            NullPolicy::new(vec![]),
+            2,
+        );
+
+        writer
+            .write_headers(&StringRecord::from(vec!["id", "name", "joined"]))
+            .unwrap();
+        for row in [
+            vec!["1", "Alice", "2024-01-02"],
+            vec!["2", "", "2024-02-03"],
+            vec!["3", "Cy", "2024-03-04"],
+        ] {
+            let outcome = writer.write_record(&StringRecord::from(row)).unwrap();
+            assert_eq!(outcome, WriteOutcome::Written);
+        }
+        writer.finish().unwrap();
+
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file.reopen().unwrap()).unwrap();
+        assert_eq!(builder.metadata().num_row_groups(), 2);
+        assert!(!builder.schema().field(0).is_nullable());
+        assert!(builder.schema().field(1).is_nullable());
+
+        let batch = builder.build().unwrap().next().unwrap().unwrap();
+        let ids = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int16Array>()
+            .unwrap();
+        let names = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let dates = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Date32Array>()
+            .unwrap();
+        assert_eq!(ids.value(1), 2);
+        assert!(names.is_null(1));
+        assert_eq!(dates.value(0), 19724); // 2024-01-02
+    }
+
+    #[test]
+    fn test_rejects_uncastable_and_null_values() {
+        let mut output = Vec::new();
+        let mut writer =
+            ParquetRecordWriter::new(&mut output, test_schema(), NullPolicy::new(vec![]), 100);
+
+        writer
+            .write_headers(&StringRecord::from(vec!["id", "name", "joined"]))
+            .unwrap();
+        let bad_cast = writer
+            .write_record(&StringRecord::from(vec!["x", "Al", "2024-01-02"]))
+            .unwrap();
+        let bad_null = writer
+            .write_record(&StringRecord::from(vec!["", "Al", "2024-01-02"]))
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert!(matches!(bad_cast, WriteOutcome::Rejected(msg) if msg.contains("SMALLINT")));
+        assert!(matches!(bad_null, WriteOutcome::Rejected(msg) if msg.contains("non-nullable")));
+    }
+
+    #[test]
+    fn test_zero_row_files_keep_schema() {
+        // A header without data rows, and no header at all
+        for headers in [Some(vec!["id", "name", "joined"]), None] {
+            let file = NamedTempFile::new().unwrap();
+            let mut writer = ParquetRecordWriter::new(
+                file.reopen().unwrap(),
+                test_schema(),
+                NullPolicy::new(vec![]),
+                100,
+            );
+            if let Some(headers) = headers {
+                writer.write_headers(&StringRecord::from(headers)).unwrap();
+            }
+            writer.finish().unwrap();
+
+            let builder = ParquetRecordBatchReaderBuilder::try_new(file.reopen().unwrap()).unwrap();
+            assert_eq!(builder.metadata().file_metadata().num_rows(), 0);
+            assert_eq!(builder.schema().fields().len(), 3);
+            assert_eq!(builder.schema().field(2).name(), "joined");
+        }
+    }
+
+    #[test]
+    fn test_scaled_decimal() {
+        assert_eq!(scaled_decimal("-1.5", 2), Some(-150));
+        assert_eq!(scaled_decimal("12", 3), Some(12000));
+        assert_eq!(scaled_decimal(".05", 2), Some(5));
+    }
+
+    #[test]
+    fn test_cells_that_columns_cannot_store() {
+        let money = ColumnBuilder::new(&SqlType::Decimal {
+            precision: 5,
+            scale: 2,
+        });
+        assert!(money.cell(&TypedValue::Decimal("1.5")).is_ok());
+        assert!(money.cell(&TypedValue::Decimal("1e5")).is_err());
+        assert!(money.cell(&TypedValue::Null).is_ok());
+
+        let ids = ColumnBuilder::new(&SqlType::Integer);
+        assert_eq!(
+            ids.cell(&TypedValue::Text("x")).err().unwrap(),
+            "Text(\"x\") does not match the Parquet column"
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use crate::analyzer::nulls::NullPolicy;
+use crate::database::DatabaseDialect;
+use crate::parser::cast::{RecordCaster, TypedValue};
+use crate::parser::output::{RecordWriter, WriteOutcome};
+use crate::types::TableSchema;
+use crate::utils::sanitize_column_name;
+use anyhow::Result;
+use csv::StringRecord;
+use std::io::Write;
+
+/// Writes records as multi-row `INSERT INTO ... VALUES` statements.
+/// Rows are streamed straight to the output, so only the current row is held in memory.
+pub struct SqlInsertWriter<W: Write> {
+    output: W,
+    dialect: Box<dyn DatabaseDialect>,
+    table: String,
+    caster: RecordCaster,
+    column_list: String,
+    batch_size: usize,
+    rows_in_batch: usize,
+}
+
+impl<W: Write> SqlInsertWriter<W> {
+    pub fn new(
+        output: W,
+        dialect: Box<dyn DatabaseDialect>,
+        table: String,
+        schema: Option<TableSchema>,
+        nulls: NullPolicy,
+        batch_size: usize,
+    ) -> Self {
+        SqlInsertWriter {
+            output,
+            dialect,
+            table,
+            caster: RecordCaster::new(schema, nulls),
+            column_list: String::new(),
+            batch_size: batch_size.max(1),
+            rows_in_batch: 0,
+        }
+    }
+
+    fn render_row(&self, record: &StringRecord) -> Result<String, String> {
+        let values: Vec<String> = self
+            .caster
+            .cast_record(record)?
+            .iter()
+            .map(|value| self.render_value(value))
+            .collect();
+
+        Ok(format!("({})", values.join(", ")))
+    }
+
+    fn render_value(&self, value: &TypedValue) -> String {
+        match value {
+            TypedValue::Null => "NULL".to_string(),
+            TypedValue::Boolean(true) => "TRUE".to_string(),
+            TypedValue::Boolean(false) => "FALSE".to_string(),
+            TypedValue::Integer(num) => num.to_string(),
+            TypedValue::Float(num) => format!("{:?}", num),
+            TypedValue::Decimal(text) => text.to_string(),
+            TypedValue::Date(_)
+            | TypedValue::Time(_)
+            | TypedValue::DateTime(_)
+            | TypedValue::DateTimeTz(_) => self
+                .dialect
+                .quote_string(&value.temporal_text().unwrap_or_default()),
+            TypedValue::Json(text) | TypedValue::Text(text) => self.dialect.quote_string(text),
+        }
+    }
+
+    fn end_statement(&mut self) -> Result<()> {
+        if self.rows_in_batch > 0 {
+            writeln!(self.output, ";")?;
+            self.rows_in_batch = 0;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> RecordWriter for SqlInsertWriter<W> {
+    fn write_headers(&mut self, headers: &StringRecord) -> Result<()> {
+        // Columns are named as in the DDL, renames included
+        let schema = self.caster.schema();
+        let columns: Vec<String> = headers
+            .iter()
+            .map(|h| match schema.and_then(|s| s.column(h)) {
+                Some(column) => column.ddl_name(),
+                None => sanitize_column_name(h),
+            })
+            .map(|name| self.dialect.quote_identifier(&name))
+            .collect();
+        self.column_list = columns.join(", ");
+
+        self.caster.set_headers(headers)?;
+
+        Ok(())
+    }
+
+    fn write_record(&mut self, record: &StringRecord) -> Result<WriteOutcome> {
+        let row = match self.render_row(record) {
+            Ok(row) => row,
+            Err(reason) => return Ok(WriteOutcome::Rejected(reason)),
+        };
+
+        if self.rows_in_batch == 0 {
+            write!(
+                self.output,
+                "INSERT INTO {} ({}) VALUES\n    {}",
+                self.dialect.quote_identifier(&self.table),
+                self.column_list,
+                row
+            )?;
+        } else {
+            write!(self.output, ",\n    {}", row)?;
+        }
+
+        self.rows_in_batch += 1;
+        if self.rows_in_batch >= self.batch_size {
+            self.end_statement()?;
+        }
+
+        Ok(WriteOutcome::Written)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.end_statement()?;
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{MySQL, PostgreSQL};
+    use crate::types::{ColumnStats, SqlType};
+
+    fn write_rows(
+        writer: &mut SqlInsertWriter<&mut Vec<u8>>,
+        headers: &[&str],
+        rows: &[&[&str]],
+    ) -> Vec<WriteOutcome> {
+        writer
+            .write_headers(&StringRecord::from(headers.to_vec()))
+            .unwrap();
+        let outcomes = rows
+            .iter()
+            .map(|row| {
+                writer
+                    .write_record(&StringRecord::from(row.to_vec()))
+                    .unwrap()
+            })
+            .collect();
+        writer.finish().unwrap();
+        outcomes
+    }
+
+    #[test]
+    fn test_batches_rows() {
+        let mut output = Vec::new();
+        let mut writer = SqlInsertWriter::new(
+            &mut output,
+            Box::new(PostgreSQL),
+            "people".to_string(),
+            None,
+            NullPolicy::new(vec!["NA".to_string()]),
+            2,
+        );
+
+        write_rows(
+            &mut writer,
+            &["id", "name"],
+            &[&["1", "O'Brien"], &["2", "NA"], &["3", "Cy"]],
+        );
+
+        let sql = String::from_utf8(output).unwrap();
+        assert_eq!(
+            sql,
+            "INSERT INTO people (id, name) VALUES\n    ('1', 'O''Brien'),\n    ('2', NULL);\n\
+             INSERT INTO people (id, name) VALUES\n    ('3', 'Cy');\n"
+        );
+    }
+
+    #[test]
+    fn test_typed_literals_and_rejects() {
+        let mut columns = vec![
+            ColumnStats::new("id".to_string()),
+            ColumnStats::new("active".to_string()),
+            ColumnStats::new("order".to_string()),
+        ];
+        columns[0].sql_type = SqlType::SmallInt;
+        columns[1].sql_type = SqlType::Boolean;
+        columns[2].sql_type = SqlType::Varchar(None);
+        columns[1].rename = Some("is_active".to_string());
+        let schema = TableSchema::new("t".to_string(), columns);
+
+        let mut output = Vec::new();
+        let mut writer = SqlInsertWriter::new(
+            &mut output,
+            Box::new(MySQL),
+            "t".to_string(),
+            Some(schema),
+            NullPolicy::new(vec![]),
+            100,
+        );
+
+        let outcomes = write_rows(
+            &mut writer,
+            &["id", "active", "order"],
+            &[&["1", "yes", "a\\b"], &["x", "no", "c"]],
+        );
+
+        assert_eq!(outcomes[0], WriteOutcome::Written);
+        assert!(matches!(&outcomes[1], WriteOutcome::Rejected(msg) if msg.contains("SMALLINT")));
+
+        let sql = String::from_utf8(output).unwrap();
+        assert_eq!(
+            sql,
+            "INSERT INTO t (id, is_active, `order`) VALUES\n    (1, TRUE, 'a\\\\b');\n"
+        );
+    }
+}
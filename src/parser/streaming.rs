@@ -1,11 +1,24 @@
 use crate::cli::ParseArgs;
+use crate::parser::output::{create_record_writer, load_schema, WriteOutcome};
+use crate::types::TableSchema;
 use anyhow::Result;
 use csv::{ReaderBuilder, StringRecord, WriterBuilder};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::PathBuf;
 
-pub fn process_csv<R: Read, W: Write>(input: R, output: W, args: &ParseArgs) -> Result<()> {
+pub fn process_csv<R: Read, W: Write + Send>(input: R, output: W, args: &ParseArgs) -> Result<()> {
+    let schema = load_schema(args)?;
+    process_csv_with_schema(input, output, args, schema)
+}
+
+/// Like `process_csv`, with the column types already resolved by the caller
+pub fn process_csv_with_schema<R: Read, W: Write + Send>(
+    input: R,
+    output: W,
+    args: &ParseArgs,
+    schema: Option<TableSchema>,
+) -> Result<()> {
     // Parse badmax - support "all" for unlimited
     let max_bad_rows = if args.badmax == "all" {
         None
@@ -32,16 +45,7 @@ pub fn process_csv<R: Read, W: Write>(input: R, output: W, args: &ParseArgs) ->
 
     let mut reader = reader_builder.from_reader(input);
 
-    let mut writer_builder = WriterBuilder::new();
-    writer_builder
-        .delimiter(args.delimiter as u8)
-        .double_quote(true); // RFC 4180 compliant double quote escaping
-
-    if let Some(quote_byte) = args.quote.as_byte() {
-        writer_builder.quote(quote_byte);
-    }
-
-    let mut writer = writer_builder.from_writer(output);
+    let mut writer = create_record_writer(output, args, schema)?;
 
     // Set up bad row writer if needed
     let mut bad_writer = if let Some(ref badfile) = args.badfile {
@@ -57,7 +61,7 @@ pub fn process_csv<R: Read, W: Write>(input: R, output: W, args: &ParseArgs) ->
     // Write headers if present and track expected field count
     if let Ok(headers) = reader.headers() {
         expected_field_count = Some(headers.len());
-        writer.write_record(headers)?;
+        writer.write_headers(headers)?;
         if let Some(ref mut bw) = bad_writer {
             // Write a different header for bad file to avoid field count mismatch
             let bad_headers = StringRecord::from(vec!["Row", "Error"]);
@@ -91,33 +95,39 @@ pub fn process_csv<R: Read, W: Write>(input: R, output: W, args: &ParseArgs) ->
                             eprintln!("Row content: {:?}", record.iter().collect::<Vec<_>>());
                         }
 
-                        // Write to bad file if configured
-                        if let Some(ref mut bw) = bad_writer {
-                            if max_bad_rows.is_none() || bad_row_count <= max_bad_rows.unwrap() {
-                                let error_record = StringRecord::from(vec![
-                                    format!("Row {}", total_rows + 1),
-                                    error_msg,
-                                ]);
-                                bw.write_record(&error_record)?;
-                            }
-                        }
-
-                        // Stop processing if we exceed badmax (unless "all")
-                        if let Some(max_bad) = max_bad_rows {
-                            if bad_row_count > max_bad {
-                                if args.verbose {
-                                    eprintln!("Maximum bad rows ({}) exceeded, stopping", max_bad);
-                                }
-                                break;
-                            }
+                        if !record_bad_row(
+                            &mut bad_writer,
+                            bad_row_count,
+                            max_bad_rows,
+                            total_rows,
+                            error_msg,
+                            args.verbose,
+                        )? {
+                            break;
                         }
                         continue; // Skip processing this record
                     }
                 }
 
-                let null_transformed = transform_nulls(&record, args);
-                let processed_record = substitute_newlines(&null_transformed, args);
-                writer.write_record(&processed_record)?;
+                let processed_record = substitute_newlines(&record, args);
+                if let WriteOutcome::Rejected(reason) = writer.write_record(&processed_record)? {
+                    bad_row_count += 1;
+
+                    let error_msg = format!("Line {}: {}", total_rows + 1, reason);
+                    eprintln!("{}", error_msg);
+                    let _ = std::io::stderr().flush(); // Ensure error message is displayed immediately
+
+                    if !record_bad_row(
+                        &mut bad_writer,
+                        bad_row_count,
+                        max_bad_rows,
+                        total_rows,
+                        error_msg,
+                        args.verbose,
+                    )? {
+                        break;
+                    }
+                }
             }
             Err(e) => {
                 bad_row_count += 1;
@@ -126,32 +136,21 @@ pub fn process_csv<R: Read, W: Write>(input: R, output: W, args: &ParseArgs) ->
                     eprintln!("Error reading row {}: {}", total_rows + 1, e);
                 }
 
-                // Write to bad file if configured
-                if let Some(ref mut bw) = bad_writer {
-                    if max_bad_rows.is_none() || bad_row_count <= max_bad_rows.unwrap() {
-                        // Write error info as a CSV record
-                        let error_record = StringRecord::from(vec![
-                            format!("Row {}", total_rows + 1),
-                            format!("{}", e),
-                        ]);
-                        bw.write_record(&error_record)?;
-                    }
-                }
-
-                // Stop processing if we exceed badmax (unless "all")
-                if let Some(max_bad) = max_bad_rows {
-                    if bad_row_count > max_bad {
-                        if args.verbose {
-                            eprintln!("Maximum bad rows ({}) exceeded, stopping", max_bad);
-                        }
-                        break;
-                    }
+                if !record_bad_row(
+                    &mut bad_writer,
+                    bad_row_count,
+                    max_bad_rows,
+                    total_rows,
+                    format!("{}", e),
+                    args.verbose,
+                )? {
+                    break;
                 }
             }
         }
     }
 
-    writer.flush()?;
+    writer.finish()?;
 
     if let Some(mut bw) = bad_writer {
         bw.flush()?;
@@ -172,6 +171,38 @@ pub fn process_csv<R: Read, W: Write>(input: R, output: W, args: &ParseArgs) ->
     Ok(())
 }
 
+/// Write a bad row to the bad file (within `--badmax`) and report whether processing
+/// should continue
+fn record_bad_row(
+    bad_writer: &mut Option<csv::Writer<File>>,
+    bad_row_count: usize,
+    max_bad_rows: Option<usize>,
+    total_rows: usize,
+    error_msg: String,
+    verbose: bool,
+) -> Result<bool> {
+    // Write to bad file if configured
+    if let Some(ref mut bw) = bad_writer {
+        if max_bad_rows.is_none_or(|max_bad| bad_row_count <= max_bad) {
+            let error_record =
+                StringRecord::from(vec![format!("Row {}", total_rows + 1), error_msg]);
+            bw.write_record(&error_record)?;
+        }
+    }
+
+    // Stop processing if we exceed badmax (unless "all")
+    if let Some(max_bad) = max_bad_rows {
+        if bad_row_count > max_bad {
+            if verbose {
+                eprintln!("Maximum bad rows ({}) exceeded, stopping", max_bad);
+            }
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
 fn create_bad_row_writer(path: &PathBuf, args: &ParseArgs) -> Result<csv::Writer<File>> {
     let file = File::create(path)?;
     let mut writer_builder = WriterBuilder::new();
@@ -226,6 +257,7 @@ pub struct ParsedCsvReader<R: Read> {
     total_rows: usize,
     expected_field_count: Option<usize>,
     finished: bool,
+    lenient: bool,
 }
 
 impl<R: Read> ParsedCsvReader<R> {
@@ -258,9 +290,17 @@ impl<R: Read> ParsedCsvReader<R> {
             total_rows: 0,
             expected_field_count: None,
             finished: false,
+            lenient: false,
         })
     }
 
+    /// Skip bad rows silently instead of reporting them and failing, for passes
+    /// whose errors are reported by a later pass over the same input
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
     pub fn headers(&mut self) -> Result<&Vec<String>> {
         if self.headers.is_none() {
             let headers = self.reader.headers()?.clone();
@@ -306,6 +346,10 @@ impl<R: Read> Iterator for ParsedCsvReader<R> {
                                 if record.len() != expected {
                                     self.bad_row_count += 1;
 
+                                    if self.lenient {
+                                        continue;
+                                    }
+
                                     // Create user-friendly error message
                                     let error_msg = format!(
                                         "Line {} has {} fields, but expected {} fields",
@@ -353,6 +397,10 @@ impl<R: Read> Iterator for ParsedCsvReader<R> {
                         Err(e) => {
                             self.bad_row_count += 1;
 
+                            if self.lenient {
+                                continue;
+                            }
+
                             let error_msg =
                                 format!("Error reading row {}: {}", self.total_rows + 1, e);
                             eprintln!("{}", error_msg);
@@ -380,7 +428,7 @@ impl<R: Read> Iterator for ParsedCsvReader<R> {
                 }
                 None => {
                     // End of input - return error if we had bad rows
-                    if self.bad_row_count > 0 {
+                    if self.bad_row_count > 0 && !self.lenient {
                         self.finished = true;
                         return Some(Err(anyhow::anyhow!(
                             "Parsing failed with {} error(s)",
@@ -397,7 +445,7 @@ impl<R: Read> Iterator for ParsedCsvReader<R> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cli::QuoteStyle;
+    use crate::cli::{BlankPolicy, DatabaseType, OutputFormat, QuoteStyle};
     use std::io::Cursor;
 
     fn default_args() -> ParseArgs {
@@ -409,6 +457,9 @@ mod tests {
             escquote: None,
             fnull: vec![],
             tnull: String::new(),
+            no_default_nulls: false,
+            fnull_column: vec![],
+            blank_values: BlankPolicy::Empty,
             badfile: None,
             badmax: "0".to_string(),
             noheader: false,
@@ -416,6 +467,17 @@ mod tests {
             encoding: "utf-8".to_string(),
             verbose: false,
             sub_newline: " ".to_string(),
+            format: OutputFormat::Csv,
+            table: None,
+            batch_size: 500,
+            database: DatabaseType::Postgres,
+            database_config: None,
+            schema: None,
+            row_group_size: 65536,
+            sheet: None,
+            range: None,
+            input_format: None,
+            flatten_separator: "_".to_string(),
         }
     }
 
@@ -545,6 +607,129 @@ mod tests {
         assert!(output_str.contains("Line1 | Line2 | Line3"));
     }
 
+    #[test]
+    fn test_sql_insert_format() {
+        let input = "id,name\n1,Alice\n2,NULL";
+        let mut output = Vec::new();
+
+        let mut args = default_args();
+        args.format = OutputFormat::SqlInsert;
+        args.table = Some("people".to_string());
+        args.fnull = vec!["NULL".to_string()];
+
+        let result = process_csv(Cursor::new(input), &mut output, &args);
+
+        assert!(result.is_ok());
+        let output_str = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output_str,
+            "INSERT INTO people (id, name) VALUES\n    ('1', 'Alice'),\n    ('2', NULL);\n"
+        );
+    }
+
+    #[test]
+    fn test_tsv_format() {
+        let input = "name,note\nAlice,\"tab\there\"\nBob,NULL";
+        let mut output = Vec::new();
+
+        let mut args = default_args();
+        args.format = OutputFormat::Tsv;
+        args.fnull = vec!["NULL".to_string()];
+        args.tnull = "\\N".to_string();
+
+        let result = process_csv(Cursor::new(input), &mut output, &args);
+
+        assert!(result.is_ok());
+        let output_str = String::from_utf8(output).unwrap();
+        assert_eq!(output_str, "name\tnote\nAlice\ttab\\there\nBob\t\\N\n");
+    }
+
+    #[test]
+    fn test_jsonl_format_uses_badmax() {
+        let input = "id,name\n1,Alice\n2\n3,Cy";
+        let mut output = Vec::new();
+
+        let mut args = default_args();
+        args.format = OutputFormat::Jsonl;
+        args.badmax = "1".to_string();
+
+        let result = process_csv(Cursor::new(input), &mut output, &args);
+
+        assert!(result.is_err());
+        let output_str = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output_str,
+            "{\"id\":\"1\",\"name\":\"Alice\"}\n{\"id\":\"3\",\"name\":\"Cy\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_rejected_rows_go_to_badfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema_path = dir.path().join("schema.json");
+        let badfile = dir.path().join("bad.csv");
+        std::fs::write(
+            &schema_path,
+            r#"{"table_name": "t", "columns": [{"name": "id", "sql_type": "SMALLINT"}]}"#,
+        )
+        .unwrap();
+
+        let input = "id\n1\nabc\n3";
+        let mut output = Vec::new();
+
+        let mut args = default_args();
+        args.format = OutputFormat::SqlInsert;
+        args.schema = Some(schema_path);
+        args.badfile = Some(badfile.clone());
+        args.badmax = "all".to_string();
+
+        let result = process_csv(Cursor::new(input), &mut output, &args);
+
+        assert!(result.is_err());
+        let output_str = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output_str,
+            "INSERT INTO t (id) VALUES\n    (1),\n    (3);\n"
+        );
+        let bad = std::fs::read_to_string(badfile).unwrap();
+        assert!(bad.contains("Row 3"));
+        assert!(bad.contains("'abc' is not a valid SMALLINT"));
+    }
+
+    #[test]
+    fn test_parquet_format_infers_schema() {
+        let input = "id,name\n1,Alice\n2\n3,NA";
+
+        let mut args = default_args();
+        args.format = OutputFormat::Parquet;
+        args.fnull = vec!["NA".to_string()];
+        args.badmax = "all".to_string();
+
+        // Without a schema there are no column types to write
+        let result = process_csv(Cursor::new(input), Vec::new(), &args);
+        assert!(result.is_err());
+
+        let schema = crate::analyzer::infer_schema(Cursor::new(input), &args, Vec::new()).unwrap();
+        assert_eq!(schema.columns[0].sql_type, crate::types::SqlType::SmallInt);
+        assert!(schema.columns[1].is_nullable());
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let result = process_csv_with_schema(
+            Cursor::new(input),
+            file.reopen().unwrap(),
+            &args,
+            Some(schema),
+        );
+
+        // The short row is still reported by the writing pass
+        assert!(result.is_err());
+        let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(
+            file.reopen().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+    }
+
     #[test]
     fn test_carriage_return_removal() {
         let input = "name,description\n\"Alice\",\"Line1\r\nLine2\"";
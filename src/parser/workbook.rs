@@ -0,0 +1,373 @@
+use crate::analyzer::patterns::{CellHint, CellHints, TypeInferencer};
+use crate::analyzer::type_set::TypeSet;
+use crate::cli::NumericIdPolicy;
+use crate::parser::CsvDialect;
+use anyhow::{bail, Context, Result};
+use calamine::{open_workbook_auto, Data, Range, Reader};
+use chrono::{NaiveTime, Timelike};
+use std::path::Path;
+
+/// Zero-based (row, column) position of a worksheet cell
+type CellPosition = (u32, u32);
+
+/// Columns in a worksheet, `A` to `XFD`
+const MAX_COLUMNS: u32 = 16384;
+
+/// Read one worksheet and render it as delimited text, so workbooks flow through
+/// the same reader, validation and inference as CSV input.
+///
+/// `sheet` is a worksheet name or a 1-based index (default: the first sheet) and
+/// `range` is an A1-style cell range such as `B2:F100`, or a single start cell.
+/// Each column also gets the hint its native cell types give, see `cell_hints`.
+pub fn read_workbook(
+    path: &Path,
+    sheet: Option<&str>,
+    range: Option<&str>,
+    dialect: &CsvDialect,
+) -> Result<(Vec<u8>, CellHints)> {
+    let mut workbook = open_workbook_auto(path)
+        .with_context(|| format!("Failed to open workbook: {}", path.display()))?;
+
+    let sheet_names = workbook.sheet_names();
+    let sheet_name = select_sheet(&sheet_names, sheet)?;
+    let cells = workbook
+        .worksheet_range(&sheet_name)
+        .with_context(|| format!("Failed to read sheet '{}'", sheet_name))?;
+
+    let cells = match range {
+        Some(range) => select_range(&cells, range)?,
+        None => cells,
+    };
+
+    Ok((range_to_csv(&cells, dialect)?, cell_hints(&cells)))
+}
+
+fn select_sheet(sheet_names: &[String], sheet: Option<&str>) -> Result<String> {
+    let Some(sheet) = sheet else {
+        return sheet_names
+            .first()
+            .cloned()
+            .context("Workbook does not contain any sheets");
+    };
+
+    if let Some(name) = sheet_names.iter().find(|name| name.as_str() == sheet) {
+        return Ok(name.clone());
+    }
+
+    match sheet.parse::<usize>() {
+        Ok(index) if (1..=sheet_names.len()).contains(&index) => Ok(sheet_names[index - 1].clone()),
+        _ => bail!(
+            "Sheet '{}' not found; available sheets: {}",
+            sheet,
+            sheet_names.join(", ")
+        ),
+    }
+}
+
+/// The cells of an A1-style `range`; a start cell alone runs to the last used cell
+fn select_range(cells: &Range<Data>, range: &str) -> Result<Range<Data>> {
+    let (start, end) = parse_cell_range(range)?;
+    Ok(match end.or(cells.end()) {
+        // A start past the last used cell selects nothing
+        Some(end) if end.0 < start.0 || end.1 < start.1 => Range::empty(),
+        Some(end) => cells.range(start, end),
+        None => Range::empty(),
+    })
+}
+
+/// Parse `A1` or `A1:D10` into zero-based (row, column) positions
+fn parse_cell_range(range: &str) -> Result<(CellPosition, Option<CellPosition>)> {
+    let (start, end) = match range.split_once(':') {
+        Some((start, end)) => (start, Some(end)),
+        None => (range, None),
+    };
+
+    let start = parse_cell(start)?;
+    let end = end.map(parse_cell).transpose()?;
+
+    if let Some(end) = end {
+        if end.0 < start.0 || end.1 < start.1 {
+            bail!("Invalid cell range '{}': end is before start", range);
+        }
+    }
+
+    Ok((start, end))
+}
+
+fn parse_cell(cell: &str) -> Result<CellPosition> {
+    let cell = cell.trim().to_ascii_uppercase();
+    let split = cell
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(cell.len());
+    let (letters, digits) = cell.split_at(split);
+
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_uppercase()) {
+        bail!("Invalid cell reference '{}'", cell);
+    }
+
+    let column = letters
+        .chars()
+        .try_fold(0u32, |acc, c| {
+            acc.checked_mul(26)?.checked_add(c as u32 - 'A' as u32 + 1)
+        })
+        .filter(|column| *column <= MAX_COLUMNS)
+        .with_context(|| format!("Invalid cell reference '{}'", cell))?;
+    let row: u32 = digits
+        .parse()
+        .ok()
+        .filter(|row| *row > 0)
+        .with_context(|| format!("Invalid cell reference '{}'", cell))?;
+
+    Ok((row - 1, column - 1))
+}
+
+fn range_to_csv(cells: &Range<Data>, dialect: &CsvDialect) -> Result<Vec<u8>> {
+    let mut writer = dialect.writer_builder().from_writer(Vec::new());
+    for row in cells.rows() {
+        writer.write_record(row.iter().map(cell_text))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| anyhow::anyhow!("Failed to buffer worksheet: {}", e))
+}
+
+/// The hint each column's data cells give below the header row: text cells
+/// that read like numbers or dates keep it text, fractional number cells make
+/// it floating point rather than an exact decimal, and a column of only
+/// boolean or only date cells keeps that type
+fn cell_hints(cells: &Range<Data>) -> CellHints {
+    let inferencer = TypeInferencer::new().with_numeric_ids(NumericIdPolicy::Never);
+    let reads_as_number_or_date = |text: &str| {
+        let types = inferencer.candidate_types(text);
+        !(types - TypeSet::VARCHAR - TypeSet::BOOLEAN - TypeSet::JSON).is_empty()
+    };
+
+    let mut columns = vec![NativeCells::default(); cells.width()];
+    for row in cells.rows().skip(1) {
+        for (column, cell) in columns.iter_mut().zip(row) {
+            match cell {
+                Data::Empty => continue,
+                Data::String(text) if reads_as_number_or_date(text) => column.text = true,
+                Data::Float(num) if num.fract() != 0.0 => column.fractions = true,
+                Data::Bool(_) => column.booleans += 1,
+                Data::DateTime(excel) if excel.is_datetime() && excel.as_f64() >= 1.0 => {
+                    column.dates += 1;
+                    column.times |= excel.as_f64().fract() != 0.0;
+                }
+                _ => {}
+            }
+            column.cells += 1;
+        }
+    }
+    columns.iter().map(NativeCells::hint).collect()
+}
+
+/// The native types of one column's non-empty cells
+#[derive(Debug, Clone, Default)]
+struct NativeCells {
+    cells: usize,
+    /// Some text cell reads like a number or date
+    text: bool,
+    /// Some number cell has a fraction
+    fractions: bool,
+    booleans: usize,
+    /// Date serials, with or without a time of day
+    dates: usize,
+    /// Some date serial has a time of day
+    times: bool,
+}
+
+impl NativeCells {
+    fn hint(&self) -> Option<CellHint> {
+        if self.text {
+            Some(CellHint::Text)
+        } else if self.fractions {
+            Some(CellHint::Float)
+        } else if self.cells == 0 {
+            None
+        } else if self.booleans == self.cells {
+            Some(CellHint::Boolean)
+        } else if self.dates == self.cells && self.times {
+            Some(CellHint::DateTime)
+        } else if self.dates == self.cells {
+            Some(CellHint::Date)
+        } else {
+            None
+        }
+    }
+}
+
+/// Render a cell in the text form the `TypeInferencer` recognizes for its native type:
+/// whole numbers without a fraction, booleans as `true`/`false`, and date cells
+/// (Excel serial numbers) as ISO DATE, TIME or DATETIME text.
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(text) | Data::DateTimeIso(text) | Data::DurationIso(text) => text.clone(),
+        Data::Int(num) => num.to_string(),
+        Data::Float(num) if num.fract() == 0.0 && num.abs() < 1e15 => (*num as i64).to_string(),
+        Data::Float(num) => num.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(excel) if excel.is_duration() => excel.as_f64().to_string(),
+        Data::DateTime(excel) => match excel.as_datetime() {
+            // Serial values below 1 carry no date, only a time of day
+            Some(datetime) if excel.as_f64() < 1.0 => format_time(datetime.time()),
+            Some(datetime) if datetime.time() == NaiveTime::MIN => {
+                datetime.format("%Y-%m-%d").to_string()
+            }
+            Some(datetime) => format!(
+                "{} {}",
+                datetime.format("%Y-%m-%d"),
+                format_time(datetime.time())
+            ),
+            None => excel.as_f64().to_string(),
+        },
+        Data::Error(error) => error.to_string(),
+    }
+}
+
+fn format_time(time: NaiveTime) -> String {
+    // Excel stores times as fractions of a day; drop sub-second float noise
+    let time = time.with_nanosecond(0).unwrap_or(time);
+    time.format("%H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::QuoteStyle;
+    use calamine::{ExcelDateTime, ExcelDateTimeType};
+
+    fn default_dialect() -> CsvDialect {
+        CsvDialect {
+            delimiter: ',',
+            quote: QuoteStyle::Double,
+            escquote: None,
+        }
+    }
+
+    fn excel_date(value: f64) -> Data {
+        Data::DateTime(ExcelDateTime::new(
+            value,
+            ExcelDateTimeType::DateTime,
+            false,
+        ))
+    }
+
+    #[test]
+    fn test_cell_text_uses_native_types() {
+        assert_eq!(cell_text(&Data::Float(42.0)), "42");
+        assert_eq!(cell_text(&Data::Float(2.5)), "2.5");
+        assert_eq!(cell_text(&Data::Bool(true)), "true");
+        assert_eq!(cell_text(&excel_date(45292.0)), "2024-01-01");
+        assert_eq!(cell_text(&excel_date(45292.5)), "2024-01-01 12:00:00");
+        assert_eq!(cell_text(&excel_date(0.25)), "06:00:00");
+    }
+
+    #[test]
+    fn test_cell_hints() {
+        let mut cells = Range::new((0, 0), (2, 6));
+        for (column, name) in ["zip", "price", "count", "flag", "active", "joined", "seen"]
+            .into_iter()
+            .enumerate()
+        {
+            cells.set_value((0, column as u32), Data::String(name.to_string()));
+        }
+        cells.set_value((1, 0), Data::String("02134".to_string()));
+        cells.set_value((2, 0), Data::Float(10001.0));
+        cells.set_value((1, 1), Data::Float(2.5));
+        cells.set_value((2, 1), Data::Float(3.0));
+        cells.set_value((1, 2), Data::Float(3.0));
+        cells.set_value((1, 3), Data::String("yes".to_string()));
+        cells.set_value((1, 4), Data::Bool(true));
+        cells.set_value((2, 4), Data::Bool(false));
+        cells.set_value((1, 5), excel_date(45292.0));
+        cells.set_value((2, 5), excel_date(45293.0));
+        cells.set_value((1, 6), excel_date(45292.0));
+        cells.set_value((2, 6), excel_date(45292.5));
+
+        assert_eq!(
+            cell_hints(&cells),
+            vec![
+                Some(CellHint::Text),
+                Some(CellHint::Float),
+                None,
+                None,
+                Some(CellHint::Boolean),
+                Some(CellHint::Date),
+                Some(CellHint::DateTime),
+            ]
+        );
+
+        let zip = TypeInferencer::new().with_cell_hint(Some(CellHint::Text));
+        assert_eq!(
+            zip.infer_type("10001"),
+            crate::types::SqlType::Varchar(Some(5))
+        );
+        let price = TypeInferencer::new()
+            .with_exact_decimals(true)
+            .with_cell_hint(Some(CellHint::Float));
+        assert_eq!(
+            price.infer_type("2.5"),
+            crate::types::SqlType::DoublePrecision
+        );
+
+        // Date serials at midnight still make a DATETIME column of date-times
+        let seen = TypeInferencer::new().with_cell_hint(Some(CellHint::DateTime));
+        assert_eq!(
+            seen.infer_type("2024-01-01"),
+            crate::types::SqlType::DateTime
+        );
+        let joined = TypeInferencer::new().with_cell_hint(Some(CellHint::Date));
+        assert_eq!(joined.infer_type("2024-01-01"), crate::types::SqlType::Date);
+        let active = TypeInferencer::new().with_cell_hint(Some(CellHint::Boolean));
+        assert_eq!(active.infer_type("true"), crate::types::SqlType::Boolean);
+    }
+
+    #[test]
+    fn test_cell_ranges() {
+        assert_eq!(parse_cell("A1").unwrap(), (0, 0));
+        assert_eq!(parse_cell("ab12").unwrap(), (11, 27));
+        assert!(parse_cell("12").is_err());
+        assert!(parse_cell("A0").is_err());
+        assert_eq!(parse_cell("XFD1").unwrap(), (0, 16383));
+        assert!(parse_cell("XFE1").is_err());
+        assert!(parse_cell("ZZZZZZZZ1").is_err());
+        assert_eq!(parse_cell_range("B2:D10").unwrap(), ((1, 1), Some((9, 3))));
+        assert_eq!(parse_cell_range("C3").unwrap(), ((2, 2), None));
+        assert!(parse_cell_range("D4:A1").is_err());
+        assert!(parse_cell_range("B2:A10").is_err());
+
+        let mut cells = Range::new((0, 0), (2, 1));
+        cells.set_value((2, 1), Data::Int(7));
+        assert_eq!(select_range(&cells, "B2").unwrap().get_size(), (2, 1));
+        assert!(select_range(&cells, "Z100").unwrap().is_empty());
+        assert!(select_range(&Range::empty(), "A1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_select_sheet_by_name_or_index() {
+        let names = vec!["Summary".to_string(), "2024".to_string()];
+        assert_eq!(select_sheet(&names, None).unwrap(), "Summary");
+        assert_eq!(select_sheet(&names, Some("2")).unwrap(), "2024");
+        assert_eq!(select_sheet(&names, Some("2024")).unwrap(), "2024");
+        assert!(select_sheet(&names, Some("3")).is_err());
+    }
+
+    #[test]
+    fn test_range_to_csv() {
+        let mut cells = Range::new((0, 0), (2, 1));
+        cells.set_value((0, 0), Data::String("name".to_string()));
+        cells.set_value((0, 1), Data::String("joined".to_string()));
+        cells.set_value((1, 0), Data::String("Smith, Al".to_string()));
+        cells.set_value((1, 1), excel_date(45292.0));
+        cells.set_value((2, 0), Data::String("Bo".to_string()));
+
+        let csv = range_to_csv(&cells, &default_dialect()).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "name,joined\n\"Smith, Al\",2024-01-01\nBo,\n"
+        );
+    }
+}
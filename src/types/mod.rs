@@ -1,16 +1,34 @@
+use crate::utils::sanitize_column_name;
+use anyhow::anyhow;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SqlType {
     Boolean,
+    /// One-byte integer, only chosen through a dialect promotion rule
+    TinyInt,
     SmallInt,
     Integer,
     BigInt,
+    /// Exact fixed-point number with `precision` total digits, `scale` after the point
+    Decimal {
+        precision: u16,
+        scale: u16,
+    },
     DoublePrecision,
     Date,
     Time,
     DateTime,
+    /// Timestamp with a time zone offset, e.g. `2024-03-01T10:00:00+02:00`
+    DateTimeTz,
+    /// JSON arrays and objects, e.g. from nested JSON Lines input
+    Json,
     Varchar(Option<usize>),
 }
 
@@ -18,14 +36,18 @@ impl SqlType {
     pub fn promotion_order(&self) -> u8 {
         match self {
             SqlType::Boolean => 0,
-            SqlType::SmallInt => 1,
-            SqlType::Integer => 2,
-            SqlType::BigInt => 3,
-            SqlType::DoublePrecision => 4,
-            SqlType::Date => 5,
-            SqlType::Time => 6,
-            SqlType::DateTime => 7,
-            SqlType::Varchar(_) => 8,
+            SqlType::TinyInt => 1,
+            SqlType::SmallInt => 2,
+            SqlType::Integer => 3,
+            SqlType::BigInt => 4,
+            SqlType::Decimal { .. } => 5,
+            SqlType::DoublePrecision => 6,
+            SqlType::Date => 7,
+            SqlType::Time => 8,
+            SqlType::DateTime => 9,
+            SqlType::DateTimeTz => 10,
+            SqlType::Json => 11,
+            SqlType::Varchar(_) => 12,
         }
     }
 
@@ -33,26 +55,49 @@ impl SqlType {
         match (self, other) {
             // Numeric promotions
             (
-                SqlType::Boolean,
+                SqlType::Boolean | SqlType::TinyInt,
                 SqlType::SmallInt
                 | SqlType::Integer
                 | SqlType::BigInt
+                | SqlType::Decimal { .. }
                 | SqlType::DoublePrecision
                 | SqlType::Varchar(_),
             ) => true,
+            (SqlType::Boolean, SqlType::TinyInt) => true,
             (
                 SqlType::SmallInt,
-                SqlType::Integer | SqlType::BigInt | SqlType::DoublePrecision | SqlType::Varchar(_),
+                SqlType::Integer
+                | SqlType::BigInt
+                | SqlType::Decimal { .. }
+                | SqlType::DoublePrecision
+                | SqlType::Varchar(_),
             ) => true,
             (
                 SqlType::Integer,
-                SqlType::BigInt | SqlType::DoublePrecision | SqlType::Varchar(_),
+                SqlType::BigInt
+                | SqlType::Decimal { .. }
+                | SqlType::DoublePrecision
+                | SqlType::Varchar(_),
+            ) => true,
+            (
+                SqlType::BigInt,
+                SqlType::Decimal { .. } | SqlType::DoublePrecision | SqlType::Varchar(_),
             ) => true,
-            (SqlType::BigInt, SqlType::DoublePrecision | SqlType::Varchar(_)) => true,
+            (SqlType::Decimal { .. }, SqlType::DoublePrecision | SqlType::Varchar(_)) => true,
             (SqlType::DoublePrecision, SqlType::Varchar(_)) => true,
 
-            // Date/time promotions to VARCHAR
-            (SqlType::Date | SqlType::Time | SqlType::DateTime, SqlType::Varchar(_)) => true,
+            // Naive timestamps can take an offset
+            (SqlType::DateTime, SqlType::DateTimeTz) => true,
+
+            // Date/time and JSON promotions to VARCHAR
+            (
+                SqlType::Date
+                | SqlType::Time
+                | SqlType::DateTime
+                | SqlType::DateTimeTz
+                | SqlType::Json,
+                SqlType::Varchar(_),
+            ) => true,
 
             // VARCHAR can accommodate larger sizes
             (SqlType::Varchar(Some(a)), SqlType::Varchar(Some(b))) => a <= b,
@@ -71,6 +116,19 @@ impl SqlType {
             return self.clone();
         }
 
+        // Widen a decimal so it still holds every integer digit of either side
+        if let (Some((int_a, scale_a)), Some((int_b, scale_b))) =
+            (self.exact_digits(), other.exact_digits())
+        {
+            if matches!(self, SqlType::Decimal { .. }) || matches!(other, SqlType::Decimal { .. }) {
+                let scale = scale_a.max(scale_b);
+                return SqlType::Decimal {
+                    precision: int_a.max(int_b).saturating_add(scale),
+                    scale,
+                };
+            }
+        }
+
         let self_order = self.promotion_order();
         let other_order = other.promotion_order();
 
@@ -100,6 +158,21 @@ impl SqlType {
         }
     }
 
+    /// Integer and fractional digits an exact numeric type can hold
+    fn exact_digits(&self) -> Option<(u16, u16)> {
+        match self {
+            SqlType::Boolean => Some((1, 0)),
+            SqlType::TinyInt => Some((3, 0)),
+            SqlType::SmallInt => Some((5, 0)),
+            SqlType::Integer => Some((10, 0)),
+            SqlType::BigInt => Some((19, 0)),
+            SqlType::Decimal { precision, scale } => {
+                Some((precision.saturating_sub(*scale), *scale))
+            }
+            _ => None,
+        }
+    }
+
     pub fn to_ddl(&self, dialect: &dyn crate::database::DatabaseDialect) -> String {
         dialect.map_type(self)
     }
@@ -109,36 +182,282 @@ impl fmt::Display for SqlType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             SqlType::Boolean => write!(f, "BOOLEAN"),
+            SqlType::TinyInt => write!(f, "TINYINT"),
             SqlType::SmallInt => write!(f, "SMALLINT"),
             SqlType::Integer => write!(f, "INTEGER"),
             SqlType::BigInt => write!(f, "BIGINT"),
+            SqlType::Decimal { precision, scale } => write!(f, "DECIMAL({},{})", precision, scale),
             SqlType::DoublePrecision => write!(f, "DOUBLE PRECISION"),
             SqlType::Date => write!(f, "DATE"),
             SqlType::Time => write!(f, "TIME"),
             SqlType::DateTime => write!(f, "DATETIME"),
+            SqlType::DateTimeTz => write!(f, "TIMESTAMPTZ"),
+            SqlType::Json => write!(f, "JSON"),
             SqlType::Varchar(Some(n)) => write!(f, "VARCHAR({})", n),
             SqlType::Varchar(None) => write!(f, "VARCHAR"),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+impl FromStr for SqlType {
+    type Err = anyhow::Error;
+
+    /// Parse the names produced by `Display`, plus a few common aliases
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_uppercase();
+
+        if let Some(inner) = upper
+            .strip_prefix("VARCHAR(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let size = inner
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| anyhow!("Invalid VARCHAR length in type: {}", s))?;
+            return Ok(SqlType::Varchar(Some(size)));
+        }
+
+        if let Some(inner) = upper
+            .strip_prefix("DECIMAL(")
+            .or_else(|| upper.strip_prefix("NUMERIC("))
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let invalid = || anyhow!("Invalid DECIMAL precision or scale in type: {}", s);
+            let (precision, scale) = match inner.split_once(',') {
+                Some((p, s)) => (p.trim(), s.trim()),
+                None => (inner.trim(), "0"),
+            };
+            let precision: u16 = precision.parse().map_err(|_| invalid())?;
+            let scale: u16 = scale.parse().map_err(|_| invalid())?;
+            if precision == 0 || scale > precision {
+                return Err(invalid());
+            }
+            return Ok(SqlType::Decimal { precision, scale });
+        }
+
+        match upper.as_str() {
+            "BOOLEAN" | "BOOL" => Ok(SqlType::Boolean),
+            "TINYINT" | "BYTEINT" => Ok(SqlType::TinyInt),
+            "SMALLINT" => Ok(SqlType::SmallInt),
+            "INTEGER" | "INT" => Ok(SqlType::Integer),
+            "BIGINT" => Ok(SqlType::BigInt),
+            "DOUBLE PRECISION" | "DOUBLE" => Ok(SqlType::DoublePrecision),
+            "DATE" => Ok(SqlType::Date),
+            "TIME" => Ok(SqlType::Time),
+            "DATETIME" | "TIMESTAMP" => Ok(SqlType::DateTime),
+            "TIMESTAMPTZ" | "TIMESTAMP WITH TIME ZONE" => Ok(SqlType::DateTimeTz),
+            "JSON" | "JSONB" => Ok(SqlType::Json),
+            "VARCHAR" | "TEXT" => Ok(SqlType::Varchar(None)),
+            _ => Err(anyhow!("Unknown SQL type: {}", s)),
+        }
+    }
+}
+
+impl Serialize for SqlType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SqlType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnStats {
     pub name: String,
+    /// Name the column is created with, from an override's `rename`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rename: Option<String>,
     pub sql_type: SqlType,
+    #[serde(default)]
     pub null_count: usize,
+    #[serde(default)]
     pub total_count: usize,
+    #[serde(default)]
     pub max_length: usize,
+    #[serde(default)]
     pub min_value: Option<String>,
+    #[serde(default)]
     pub max_value: Option<String>,
+    #[serde(default)]
     pub sample_values: Vec<String>,
-    pub type_promotions: Vec<String>,
+    /// Other notes on the column's type, such as an assumed date format
+    #[serde(
+        default,
+        alias = "type_promotions",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub notes: Vec<String>,
+    /// How many values had each type on their own, with examples, narrowest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub type_evidence: Vec<TypeEvidence>,
+    /// Each change of the column type while streaming, with the value that caused it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub promotions: Vec<TypePromotion>,
+    /// Nullability declared by a typed source such as a Parquet schema;
+    /// when unset it is inferred from `null_count`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<bool>,
+    /// Fractional-seconds digits of TIME and DATETIME values, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_precision: Option<u8>,
+    /// chrono format that reads every value of a DATE column, e.g. `%d/%m/%Y`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+    /// Values that look like NULL but were analyzed as data, with their counts
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub null_like_values: BTreeMap<String, usize>,
+    /// Values that look like stand-ins for NULL, such as `-999` or `N/A`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sentinels: Vec<Sentinel>,
+    /// The type the column would get with its sentinels read as NULL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_without_sentinels: Option<SqlType>,
+    /// Values `--tolerance` let the column reject instead of widening its type
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_rejects: Vec<ExpectedReject>,
+    /// Number of distinct non-null values, when they were counted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distinct: Option<DistinctCount>,
+    /// Every value of a low-cardinality text column, with `--constraints categorical`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    /// Most frequent values, with `describe --top`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_values: Vec<ValueCount>,
+    /// Equi-width buckets of a numeric or temporal column, with `describe --top`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub histogram: Vec<HistogramBucket>,
+    /// Mean, spread and quantiles of a numeric column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numeric: Option<NumericSummary>,
+    /// Character lengths of the non-null values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<LengthSummary>,
+}
+
+/// Distribution of a numeric column's values; quantiles are approximate
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NumericSummary {
+    pub mean: f64,
+    /// Sample standard deviation, for two or more values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub std_dev: Option<f64>,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+/// Shortest, average and longest value in characters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LengthSummary {
+    pub min: usize,
+    pub avg: f64,
+    pub max: usize,
+}
+
+/// A frequent value and how often it was seen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+    /// How much the true count may exceed `count`, for columns with many values
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub error: usize,
+}
+
+/// Values from `low` up to `high`, bounds written in the column's type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    pub low: String,
+    pub high: String,
+    pub count: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// Distinct values of a column, estimated once there are more than `--exact-distinct`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DistinctCount {
+    pub count: usize,
+    pub exact: bool,
+}
+
+/// Columns whose values together are present and distinct in every row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyCandidate {
+    pub columns: Vec<String>,
+    /// Rows checked, each with a distinct value
+    pub rows: usize,
+    /// Emitted as the PRIMARY KEY rather than as UNIQUE
+    pub primary: bool,
+}
+
+/// The values of a column whose own narrowest type is `sql_type`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeEvidence {
+    /// Sized to these values alone, e.g. VARCHAR(3) for `N/A`
+    pub sql_type: SqlType,
+    pub count: usize,
+    /// Data row of the first such value, counting from 1
+    pub first_row: usize,
+    pub examples: Vec<String>,
+}
+
+/// A change of a column's type caused by one value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypePromotion {
+    pub row: usize,
+    pub from: SqlType,
+    pub to: SqlType,
+    pub value: String,
+    /// Promotion rule, rejected candidates and other reasons, if any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+}
+
+impl fmt::Display for TypePromotion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Row {}: promoted from {} to {} due to value: '{}'",
+            self.row, self.from, self.to, self.value
+        )?;
+        if !self.reasons.is_empty() {
+            write!(f, " ({})", self.reasons.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A value a column's type cannot hold, which `parse --schema` will reject
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpectedReject {
+    /// Input line, counting the header as line 1
+    pub line: usize,
+    pub value: String,
+}
+
+/// A column value that probably means "missing"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sentinel {
+    pub value: String,
+    pub count: usize,
+    /// Why it looks like a sentinel
+    pub reason: String,
 }
 
 impl ColumnStats {
     pub fn new(name: String) -> Self {
         ColumnStats {
             name,
+            rename: None,
             sql_type: SqlType::Boolean, // Start with most restrictive type
             null_count: 0,
             total_count: 0,
@@ -146,7 +465,31 @@ impl ColumnStats {
             min_value: None,
             max_value: None,
             sample_values: Vec::new(),
-            type_promotions: Vec::new(),
+            notes: Vec::new(),
+            type_evidence: Vec::new(),
+            promotions: Vec::new(),
+            nullable: None,
+            time_precision: None,
+            date_format: None,
+            null_like_values: BTreeMap::new(),
+            sentinels: Vec::new(),
+            type_without_sentinels: None,
+            expected_rejects: Vec::new(),
+            distinct: None,
+            categories: Vec::new(),
+            top_values: Vec::new(),
+            histogram: Vec::new(),
+            numeric: None,
+            length: None,
+        }
+    }
+
+    /// The column's name in DDL and INSERT statements; a renamed column is
+    /// used as given, without sanitizing
+    pub fn ddl_name(&self) -> String {
+        match &self.rename {
+            Some(name) => name.clone(),
+            None => sanitize_column_name(&self.name),
         }
     }
 
@@ -158,7 +501,145 @@ impl ColumnStats {
         }
     }
 
+    /// Distinct values per non-null value, when distinct values were counted
+    pub fn cardinality_ratio(&self) -> Option<f64> {
+        let non_null = self.total_count - self.null_count;
+        let distinct = self.distinct?;
+        (non_null > 0).then(|| distinct.count as f64 / non_null as f64)
+    }
+
     pub fn is_nullable(&self) -> bool {
-        self.null_count > 0
+        self.nullable.unwrap_or(self.null_count > 0)
+    }
+}
+
+/// Machine-readable table description written by `describe --json` and read
+/// back by `parse --schema`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSchema {
+    pub table_name: String,
+    pub columns: Vec<ColumnStats>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<KeyCandidate>,
+}
+
+impl TableSchema {
+    pub fn new(table_name: String, columns: Vec<ColumnStats>) -> Self {
+        TableSchema {
+            table_name,
+            columns,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Key candidates found in the data
+    pub fn with_keys(mut self, keys: Vec<KeyCandidate>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let schema: TableSchema = serde_json::from_str(&content)?;
+        Ok(schema)
+    }
+
+    pub fn column(&self, name: &str) -> Option<&ColumnStats> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_type_round_trip() {
+        let types = [
+            SqlType::Boolean,
+            SqlType::TinyInt,
+            SqlType::SmallInt,
+            SqlType::Integer,
+            SqlType::BigInt,
+            SqlType::Decimal {
+                precision: 12,
+                scale: 2,
+            },
+            SqlType::DoublePrecision,
+            SqlType::Date,
+            SqlType::Time,
+            SqlType::DateTime,
+            SqlType::DateTimeTz,
+            SqlType::Json,
+            SqlType::Varchar(Some(12)),
+            SqlType::Varchar(None),
+        ];
+
+        for sql_type in types {
+            assert_eq!(sql_type.to_string().parse::<SqlType>().unwrap(), sql_type);
+        }
+    }
+
+    #[test]
+    fn test_sql_type_aliases() {
+        assert_eq!("int".parse::<SqlType>().unwrap(), SqlType::Integer);
+        assert_eq!("timestamp".parse::<SqlType>().unwrap(), SqlType::DateTime);
+        assert_eq!(
+            "timestamp with time zone".parse::<SqlType>().unwrap(),
+            SqlType::DateTimeTz
+        );
+        assert_eq!("text".parse::<SqlType>().unwrap(), SqlType::Varchar(None));
+        assert_eq!(
+            "numeric(10)".parse::<SqlType>().unwrap(),
+            SqlType::Decimal {
+                precision: 10,
+                scale: 0
+            }
+        );
+        assert!("DECIMAL(2,3)".parse::<SqlType>().is_err());
+        assert!("VARCHAR(abc)".parse::<SqlType>().is_err());
+        assert!("BLOB".parse::<SqlType>().is_err());
+    }
+
+    #[test]
+    fn test_decimal_promotion_keeps_integer_digits() {
+        let money = SqlType::Decimal {
+            precision: 5,
+            scale: 2,
+        };
+
+        assert_eq!(
+            SqlType::BigInt.promote(&money),
+            SqlType::Decimal {
+                precision: 21,
+                scale: 2
+            }
+        );
+        assert_eq!(
+            money.promote(&SqlType::Decimal {
+                precision: 4,
+                scale: 3
+            }),
+            SqlType::Decimal {
+                precision: 6,
+                scale: 3
+            }
+        );
+        assert_eq!(
+            money.promote(&SqlType::DoublePrecision),
+            SqlType::DoublePrecision
+        );
+        assert_eq!(money.promote(&SqlType::Date), SqlType::Varchar(None));
+    }
+
+    #[test]
+    fn test_schema_from_minimal_json() {
+        let json = r#"{"table_name": "t", "columns": [{"name": "id", "sql_type": "INTEGER"}]}"#;
+        let schema: TableSchema = serde_json::from_str(json).unwrap();
+
+        let column = schema.column("id").unwrap();
+        assert_eq!(column.sql_type, SqlType::Integer);
+        assert_eq!(column.null_count, 0);
+        assert!(schema.column("missing").is_none());
     }
 }
@@ -1 +1,37 @@
 // Utilities module
+use std::path::Path;
+
+/// Derive a table name from the input file stem, falling back to `imported_table`
+pub fn default_table_name(input_path: Option<&Path>) -> String {
+    if let Some(path) = input_path {
+        path.file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or("imported_table")
+            .replace(" ", "_")
+            .replace("-", "_")
+    } else {
+        "imported_table".to_string()
+    }
+}
+
+/// Make a header usable as an unquoted column name
+pub fn sanitize_column_name(name: &str) -> String {
+    // Replace spaces and special characters with underscores
+    let sanitized = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    // Ensure it starts with a letter or underscore
+    if sanitized.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+        format!("_{}", sanitized)
+    } else {
+        sanitized
+    }
+}
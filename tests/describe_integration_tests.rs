@@ -81,6 +81,234 @@ fn test_mysql_ddl() {
     assert!(stdout.contains("test_col DOUBLE NOT NULL"));
 }
 
+#[test]
+fn test_exact_decimal_ddl() {
+    let csv_data = "amount\n1234.5\n-0.25\n17\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    for (database, expected) in [
+        ("postgres", "amount NUMERIC(6,2) NOT NULL"),
+        ("mysql", "amount DECIMAL(6,2) NOT NULL"),
+    ] {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "describe",
+                "-i",
+                temp_file.path().to_str().unwrap(),
+                "--ddl",
+                "--decimal-mode",
+                "exact",
+                "--database",
+                database,
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains(expected), "{}", stdout);
+    }
+}
+
+#[test]
+fn test_integers_beyond_bigint_ddl() {
+    // 40 digits: within PostgreSQL's NUMERIC, beyond Netezza's 38
+    let csv_data = "account\n1234567890123456789012345678901234567890\n42\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    for (database, expected) in [
+        ("postgres", "account NUMERIC(40,0) NOT NULL"),
+        ("netezza", "account VARCHAR(40) NOT NULL"),
+    ] {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "describe",
+                "-i",
+                temp_file.path().to_str().unwrap(),
+                "--ddl",
+                "--database",
+                database,
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains(expected), "{}", stdout);
+    }
+}
+
+#[test]
+fn test_numeric_ids_as_text() {
+    let csv_data = "zip,sku\n02134,1001\n90210,2002\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    for (policy, expected) in [
+        ("leading-zeros", ["zip VARCHAR(5)", "sku SMALLINT"]),
+        ("fixed-width", ["zip VARCHAR(5)", "sku VARCHAR(4)"]),
+        ("never", ["zip INTEGER", "sku SMALLINT"]),
+    ] {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "describe",
+                "-i",
+                temp_file.path().to_str().unwrap(),
+                "--ddl",
+                "--numeric-ids-as-text",
+                policy,
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        for column in expected {
+            assert!(stdout.contains(column), "{}: {}", policy, stdout);
+        }
+    }
+}
+
+#[test]
+fn test_fractional_seconds_ddl() {
+    let csv_data =
+        "started,at\n2024-03-01 10:00:00.123456,12:00:00.5\n2024-03-01 11:00:00,13:00:00\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    for (database, expected) in [
+        ("mysql", ["started DATETIME(6)", "at TIME(1)"]),
+        ("postgres", ["started TIMESTAMP(6)", "at TIME(1)"]),
+        (
+            "netezza",
+            ["started TIMESTAMP NOT NULL", "at TIME NOT NULL"],
+        ),
+    ] {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "describe",
+                "-i",
+                temp_file.path().to_str().unwrap(),
+                "--ddl",
+                "--database",
+                database,
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        for column in expected {
+            assert!(stdout.contains(column), "{}: {}", database, stdout);
+        }
+    }
+}
+
+#[test]
+fn test_date_format_discovery() {
+    let csv_data = "day,iso\n03/04/2024,2024-04-03\n25/04/2024,2024-04-25\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--json",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(schema["columns"][0]["sql_type"], "DATE");
+    assert_eq!(schema["columns"][0]["date_format"], "%d/%m/%Y");
+    assert_eq!(schema["columns"][1]["date_format"], "%Y-%m-%d");
+}
+
+#[test]
+fn test_column_overrides() {
+    let csv_data = "status_code,is_active,note\n200,1,ok\n404,-,missing\n";
+    let overrides = r#"
+[[overrides]]
+column = "status_code"
+type = "VARCHAR(3)"
+
+[[overrides]]
+pattern = "^is_"
+type = "SMALLINT"
+not_null = true
+null_values = ["-"]
+rename = "active"
+"#;
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+    let mut overrides_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    overrides_file.write_all(overrides.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--ddl",
+            "--overrides",
+            overrides_file.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stdout.contains("status_code VARCHAR(3) NOT NULL"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("active SMALLINT NOT NULL"), "{}", stdout);
+    assert!(
+        stderr.contains("Warning: column 'is_active' is forced NOT NULL, but has 1 null values"),
+        "{}",
+        stderr
+    );
+
+    // The schema keeps the rename for parse
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--json",
+            "--overrides",
+            overrides_file.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["columns"][1]["name"], "is_active");
+    assert_eq!(schema["columns"][1]["rename"], "active");
+}
+
 #[test]
 fn test_null_handling() {
     let csv_data = "nullable_col\n123\n\nNULL\n456\n";
@@ -106,6 +334,154 @@ fn test_null_handling() {
     assert!(stdout.contains("SMALLINT"));
 }
 
+#[test]
+fn test_explicit_null_tokens() {
+    let csv_data = "id,comment,score\n1,,10\n2,NULL,-\n3,N/A,\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--no-default-nulls",
+            "--fnull",
+            "NULL",
+            "--fnull-column",
+            "score=-",
+            "--fnull-column",
+            "score=",
+            "--json",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let comment = &schema["columns"][1];
+    let score = &schema["columns"][2];
+
+    // The empty comment is a value distinct from NULL, and reported as null-like
+    assert_eq!(comment["null_count"], 1);
+    assert_eq!(comment["null_like_values"][""], 1);
+    assert_eq!(comment["null_like_values"]["N/A"], 1);
+    assert_eq!(score["null_count"], 2);
+    assert_eq!(score["sql_type"], "SMALLINT");
+}
+
+#[test]
+fn test_sentinel_report() {
+    let csv_data = "age,amount\n34,10\n-999,250\n27,N/A\n45,37\n-999,1200\n61,N/A\n52,88\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("age: '-999' x2, repeats far below the other values"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("amount: 'N/A' x2, turns SMALLINT into VARCHAR(4)"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("as NULL: SMALLINT instead of VARCHAR(4)"));
+    assert!(
+        stdout.contains("Suggested: --fnull-column age=-999 --fnull-column amount=N/A"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_tolerance_keeps_narrow_type() {
+    let csv_data = "amount,day\n10,2024-01-01\nabc,2024-01-02\n40000,2024-01-03\n7,2024-01-04\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    for (tolerance, expected) in [("1", "INTEGER"), ("25%", "INTEGER"), ("0", "VARCHAR(5)")] {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "describe",
+                "-i",
+                temp_file.path().to_str().unwrap(),
+                "--tolerance",
+                tolerance,
+                "--json",
+            ])
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let amount = &schema["columns"][0];
+        assert_eq!(amount["sql_type"], expected, "{}", tolerance);
+        if expected == "INTEGER" {
+            assert_eq!(amount["expected_rejects"][0]["line"], 3);
+            assert_eq!(amount["expected_rejects"][0]["value"], "abc");
+        } else {
+            assert!(amount.get("expected_rejects").is_none());
+        }
+    }
+}
+
+#[test]
+fn test_type_evidence() {
+    let csv_data = "amount\n10\n250\nN/A\n37\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--json",
+            "-v",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    // Verbose evidence goes to stderr, leaving the JSON on stdout intact
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let amount = &schema["columns"][0];
+    assert_eq!(amount["type_evidence"][0]["sql_type"], "SMALLINT");
+    assert_eq!(amount["type_evidence"][0]["count"], 3);
+    assert_eq!(amount["type_evidence"][1]["examples"][0], "N/A");
+    assert_eq!(amount["promotions"][0]["row"], 3);
+    assert_eq!(amount["promotions"][0]["to"], "VARCHAR(3)");
+    assert!(
+        stderr.contains("Type evidence for column 'amount' (VARCHAR(3)):"),
+        "{}",
+        stderr
+    );
+}
+
 #[test]
 fn test_date_inference() {
     let csv_data = "date_col\n2023-01-15\n2023-02-20\n2023-03-10\n";
@@ -225,3 +601,547 @@ fn test_stdin_input() {
     assert!(stdout.contains("test"));
     assert!(stdout.contains("SMALLINT"));
 }
+
+#[test]
+fn test_json_schema_feeds_sql_insert() {
+    let csv_data = "id,name,active\n1,Alice,yes\n2,O'Brien,no\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+    let schema_file = NamedTempFile::new().unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--json",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\"sql_type\": \"SMALLINT\""));
+    std::fs::write(schema_file.path(), &stdout).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "parse",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--format",
+            "sql-insert",
+            "--table",
+            "people",
+            "--schema",
+            schema_file.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("INSERT INTO people (id, name, active) VALUES"));
+    assert!(stdout.contains("(1, 'Alice', TRUE)"));
+    assert!(stdout.contains("(2, 'O''Brien', FALSE);"));
+}
+
+#[test]
+fn test_json_schema_round_trip_keeps_nulls() {
+    let csv_data = "id,score,note\n1,10,a\n2,,NULL\n3,-,c\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+    let schema_file = NamedTempFile::new().unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--fnull-column",
+            "score=-",
+            "--json",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(schema["columns"][1]["sql_type"], "SMALLINT");
+    std::fs::write(schema_file.path(), &stdout).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "parse",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--format",
+            "sql-insert",
+            "--table",
+            "t",
+            "--fnull-column",
+            "score=-",
+            "--schema",
+            schema_file.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("(1, 10, 'a')"));
+    assert!(stdout.contains("(2, NULL, NULL)"));
+    assert!(stdout.contains("(3, NULL, 'c');"));
+}
+
+#[test]
+fn test_workbook_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("people.xlsx");
+
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let date_format = rust_xlsxwriter::Format::new().set_num_format("yyyy-mm-dd");
+    workbook.add_worksheet().set_name("Notes").unwrap();
+    let sheet = workbook.add_worksheet().set_name("People").unwrap();
+    sheet.write_string(0, 0, "title").unwrap();
+    sheet.write_string(2, 1, "id").unwrap();
+    sheet.write_string(2, 2, "joined").unwrap();
+    sheet.write_string(2, 3, "active").unwrap();
+    for (i, day) in [1u8, 2].iter().enumerate() {
+        let row = 3 + i as u32;
+        sheet.write_number(row, 1, (i + 1) as f64).unwrap();
+        let date = rust_xlsxwriter::ExcelDateTime::from_ymd(2024, 1, *day).unwrap();
+        sheet
+            .write_datetime_with_format(row, 2, &date, &date_format)
+            .unwrap();
+        sheet.write_boolean(row, 3, i == 0).unwrap();
+    }
+    workbook.save(&path).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            path.to_str().unwrap(),
+            "--sheet",
+            "People",
+            "--range",
+            "B3:D5",
+            "--ddl",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("id SMALLINT NOT NULL"));
+    assert!(stdout.contains("joined DATE NOT NULL"));
+    assert!(stdout.contains("active BOOLEAN NOT NULL"));
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "parse",
+            "-i",
+            path.to_str().unwrap(),
+            "--sheet",
+            "2",
+            "--range",
+            "B3",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout,
+        "id,joined,active\n1,2024-01-01,true\n2,2024-01-02,false\n"
+    );
+}
+
+#[test]
+fn test_jsonl_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("events.jsonl");
+    std::fs::write(
+        &path,
+        "{\"id\": 1, \"user\": {\"name\": \"Al\"}, \"tags\": [\"a\"]}\n\
+         {\"id\": 2, \"user\": {\"name\": null}, \"tags\": [], \"late\": true}\n\
+         {\"id\": 3, \"user\": {\"name\": \"\"}, \"tags\": [], \"late\": false}\n",
+    )
+    .unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            path.to_str().unwrap(),
+            "--ddl",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("id SMALLINT NOT NULL"));
+    assert!(stdout.contains("user_name VARCHAR(2),"));
+    assert!(stdout.contains("tags JSONB NOT NULL"));
+    assert!(stdout.contains("late BOOLEAN"));
+
+    // JSON null and missing keys stay NULL when empty strings are values
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            path.to_str().unwrap(),
+            "--no-default-nulls",
+            "--json",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["columns"][1]["null_count"], 1);
+    assert_eq!(schema["columns"][3]["null_count"], 1);
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "parse",
+            "-i",
+            path.to_str().unwrap(),
+            "--flatten-separator",
+            ".",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout,
+        "id,user.name,tags,late\n1,Al,\"[\"\"a\"\"]\",\n2,,[],true\n3,,[],false\n"
+    );
+}
+
+#[test]
+fn test_jsonl_literal_null_token() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notes.jsonl");
+    std::fs::write(
+        &path,
+        "{\"s\": \"\\\\N\"}\n{\"s\": null}\n{\"s\": \"ab\"}\n",
+    )
+    .unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            path.to_str().unwrap(),
+            "--json",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    // Only the JSON null is NULL; the string "\\N" stays a value
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["columns"][0]["null_count"], 1);
+    assert_eq!(schema["columns"][0]["min_value"], "\\N");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "parse", "-i", path.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, "s\n\\N\n\"\"\nab\n");
+}
+
+#[test]
+fn test_min_max_by_type() {
+    let csv_data = "qty,shipped\n9,31/12/2023\n10,02/01/2024\n100,15/06/2023\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--json",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let qty = &schema["columns"][0];
+    assert_eq!(
+        (&qty["min_value"], &qty["max_value"]),
+        (&"9".into(), &"100".into())
+    );
+    let shipped = &schema["columns"][1];
+    assert_eq!(shipped["min_value"], "15/06/2023");
+    assert_eq!(shipped["max_value"], "02/01/2024");
+}
+
+#[test]
+fn test_distinct_counts() {
+    let mut csv_data = String::from("id,status\n");
+    for i in 0..3000 {
+        csv_data.push_str(&format!("{},{}\n", i, ["open", "closed"][i % 2]));
+    }
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--json",
+            "--exact-distinct",
+            "100",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let id = &schema["columns"][0]["distinct"];
+    assert_eq!(id["exact"], false);
+    let estimate = id["count"].as_u64().unwrap() as f64;
+    assert!((estimate - 3000.0).abs() / 3000.0 < 0.03, "{}", estimate);
+    let status = &schema["columns"][1]["distinct"];
+    assert_eq!(
+        (&status["count"], &status["exact"]),
+        (&2.into(), &true.into())
+    );
+}
+
+#[test]
+fn test_top_values_and_histogram() {
+    let mut csv_data = String::from("status,amount\n");
+    for i in 0..60 {
+        csv_data.push_str(&format!("{},{}\n", ["open", "closed", "open"][i % 3], i));
+    }
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--json",
+            "--top",
+            "2",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let status = &schema["columns"][0];
+    assert_eq!(status["top_values"][0]["value"], "open");
+    assert_eq!(status["top_values"][0]["count"], 40);
+    assert!(status.get("histogram").is_none());
+    let histogram = schema["columns"][1]["histogram"].as_array().unwrap();
+    assert_eq!(histogram.len(), 10);
+    assert_eq!(histogram[0]["low"], "0");
+    assert_eq!(histogram[9]["high"], "59");
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--top",
+            "2",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("status: 'open' x40, 'closed' x20"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Histogram of amount:"), "{}", stdout);
+}
+
+#[test]
+fn test_numeric_and_length_statistics() {
+    let csv_data = "price,code\n2,AB\n4,ABCD\n4,A\n4,ABC\n5,ü\n5,AB\n7,AB\n9,ABCDE\n";
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--json",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let price = &schema["columns"][0];
+    assert_eq!(price["numeric"]["mean"], 5.0);
+    let std_dev = price["numeric"]["std_dev"].as_f64().unwrap();
+    assert!((std_dev - 2.138).abs() < 0.001, "{}", std_dev);
+    let code = &schema["columns"][1];
+    assert!(code.get("numeric").is_none());
+    // Lengths count characters, so 'ü' is one long
+    assert_eq!(code["length"]["min"], 1);
+    assert_eq!(code["length"]["max"], 5);
+    assert_eq!(code["length"]["avg"], 2.5);
+}
+
+#[test]
+fn test_categorical_constraints() {
+    let mut csv_data = String::from("id,status\n");
+    for i in 1..=30 {
+        csv_data.push_str(&format!("{},{}\n", i, ["open", "closed", "open"][i % 3]));
+    }
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    for (database, extra, expected) in [
+        (
+            "postgres",
+            None,
+            "status VARCHAR(6) NOT NULL CHECK (status IN ('closed', 'open'))",
+        ),
+        ("mysql", None, "status ENUM('closed', 'open') NOT NULL"),
+        (
+            "postgres",
+            Some("--enum-types"),
+            "_status_enum\" AS ENUM ('closed', 'open');",
+        ),
+    ] {
+        let mut args = vec![
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--ddl",
+            "--constraints",
+            "categorical",
+            "--database",
+            database,
+        ];
+        args.extend(extra);
+        let output = Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains(expected), "{}: {}", database, stdout);
+        // The ids are all distinct, so they get no constraint
+        assert!(!stdout.contains("CHECK (id"), "{}", stdout);
+    }
+}
+
+#[test]
+fn test_key_candidates() {
+    // `id` is a key; `region` and `code` only are together, and `note` has NULLs
+    let mut csv_data = String::from("id,region,code,note\n");
+    for i in 0..40 {
+        let note = if i == 7 {
+            String::new()
+        } else {
+            format!("n{}", i)
+        };
+        csv_data.push_str(&format!(
+            "{},{},{},{}\n",
+            i,
+            ["us", "eu"][i % 2],
+            i / 2,
+            note
+        ));
+    }
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--ddl",
+            "--constraints",
+            "keys",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("    PRIMARY KEY (id)\n);"), "{}", stdout);
+    assert!(!stdout.contains("UNIQUE"), "{}", stdout);
+
+    // Without a single-column key, the pair is the key
+    let mut csv_data = String::from("region,code,note\n");
+    for i in 0..40 {
+        csv_data.push_str(&format!("{},{},n\n", ["us", "eu"][i % 2], i / 2));
+    }
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(csv_data.as_bytes()).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "describe",
+            "-i",
+            temp_file.path().to_str().unwrap(),
+            "--json",
+            "--constraints",
+            "keys",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let keys = schema["keys"].as_array().unwrap();
+    assert_eq!(keys.len(), 1, "{}", schema["keys"]);
+    assert_eq!(keys[0]["columns"], serde_json::json!(["region", "code"]));
+    assert_eq!(keys[0]["rows"], 40);
+    assert_eq!(keys[0]["primary"], true);
+}